@@ -1,23 +1,156 @@
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use winnow::Result;
-use winnow::ascii::{newline, space0, space1};
-use winnow::combinator::{alt, separated};
-use winnow::combinator::{opt, seq};
-use winnow::error::ContextError;
+use winnow::ascii::{dec_uint, digit1, escaped, line_ending, multispace0, space0, space1};
+use winnow::combinator::{alt, eof, not, peek, separated};
+use winnow::combinator::{delimited, opt, preceded, repeat, separated_pair, terminated};
+use winnow::error::{ContextError, StrContext, StrContextValue};
 use winnow::prelude::*;
-use winnow::token::{rest, take_till, take_until, take_while};
+use winnow::stream::{AsChar, Stream};
+use winnow::token::{one_of, rest, take_till, take_until, take_while};
 use winnow_parse_error::ParseError;
 
-const EMPTY_DESCRIPTION: &str = "(no description set)";
+/// What jj prints in place of a missing description
+pub const EMPTY_DESCRIPTION: &str = "(no description set)";
+const WORKING_COPY_LABEL: &str = "Working copy";
+const PARENT_LABEL: &str = "Parent commit";
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
-enum FileStatus {
+/// Customizes how `jj status` output is recognized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Label in front of the working copy line, e.g. `Working copy` in `Working copy : ...`
+    pub working_copy_label: String,
+    /// Label in front of the parent commit line, e.g. `Parent commit` in `Parent commit: ...`
+    pub parent_label: String,
+    /// Join indented continuation lines of a description with a space, undoing jj wrapping
+    /// descriptions at the terminal width. Off by default, keeping the line breaks
+    pub unwrap_descriptions: bool,
+    /// Strip a leading `@/`, jj's repo root prefix, from file paths. Off by default
+    pub strip_root_prefix: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            working_copy_label: WORKING_COPY_LABEL.to_string(),
+            parent_label: PARENT_LABEL.to_string(),
+            unwrap_descriptions: false,
+            strip_root_prefix: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    // The colon is matched separately, so tolerate labels given as `WC:`
+    fn working_copy_label(&self) -> &str {
+        self.working_copy_label.trim_end_matches(':').trim_end()
+    }
+
+    fn parent_label(&self) -> &str {
+        self.parent_label.trim_end_matches(':').trim_end()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The input doesn't match the `jj status` format
+    Parse {
+        /// Byte offset into the input where parsing failed, counting any ANSI color codes
+        offset: usize,
+        /// The section of the status being parsed, e.g. "parent commit"
+        label: Option<&'static str>,
+        source: ParseError,
+    },
+    /// The input was empty or only whitespace, e.g. a shell pipeline that produced no output
+    EmptyInput,
+    /// The input bytes aren't valid UTF-8
+    InvalidUtf8 { source: std::str::Utf8Error },
+    /// Reading the input failed, see `Status::from_reader`
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl Error {
+    fn from_parse(error: winnow::error::ParseError<&str, ContextError>) -> Self {
+        let offset = error.offset();
+        // Contexts are pushed innermost first, so the last label is the enclosing section
+        let label = error
+            .inner()
+            .context()
+            .filter_map(|context| match context {
+                StrContext::Label(label) => Some(*label),
+                _ => None,
+            })
+            .last();
+        Self::Parse {
+            offset,
+            label,
+            source: ParseError::from_parse(error),
+        }
+    }
+
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::Parse { offset, .. } => Some(*offset),
+            Self::EmptyInput | Self::Io { .. } => None,
+            Self::InvalidUtf8 { source } => Some(source.valid_up_to()),
+        }
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            Self::Parse { label, .. } => *label,
+            Self::EmptyInput | Self::InvalidUtf8 { .. } | Self::Io { .. } => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse {
+                offset,
+                label: Some(label),
+                source,
+            } => write!(f, "failed to parse {label} at byte {offset}\n{source}"),
+            Self::Parse {
+                offset,
+                label: None,
+                source,
+            } => write!(f, "failed to parse at byte {offset}\n{source}"),
+            Self::EmptyInput => write!(f, "input is empty, expected `jj status` output"),
+            Self::InvalidUtf8 { source } => write!(f, "input is not valid UTF-8: {source}"),
+            Self::Io { message, .. } => write!(f, "failed to read input: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse { source, .. } => Some(source),
+            Self::InvalidUtf8 { source } => Some(source),
+            Self::EmptyInput | Self::Io { .. } => None,
+        }
+    }
+}
+
+/// Ordered Added < Modified < Deleted < Renamed < Copied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FileStatus {
     Added,
     Modified,
-    Removed,
+    Deleted,
+    Renamed,
+    Copied,
 }
 
 impl Display for FileStatus {
@@ -25,118 +158,654 @@ impl Display for FileStatus {
         let symbol = match self {
             FileStatus::Added => 'A',
             FileStatus::Modified => 'M',
-            FileStatus::Removed => 'R',
+            FileStatus::Deleted => 'D',
+            FileStatus::Renamed => 'R',
+            FileStatus::Copied => 'C',
         };
         write!(f, "{symbol}")
     }
 }
 
+impl FileStatus {
+    /// Git's two-character porcelain code for this status.
+    /// jj has no staging area, so the status always goes in the index column.
+    pub fn to_git_xy(&self) -> (char, char) {
+        let x = match self {
+            FileStatus::Added => 'A',
+            FileStatus::Modified => 'M',
+            FileStatus::Deleted => 'D',
+            FileStatus::Renamed => 'R',
+            FileStatus::Copied => 'C',
+        };
+        (x, ' ')
+    }
+
+    /// Map git's two-character porcelain code onto a jj status.
+    ///
+    /// This is lossy: the index column wins over the worktree column, a type
+    /// change (`T`) is treated as a modification, and codes jj has no
+    /// equivalent for (untracked, ignored, unmerged) yield `None`.
+    pub fn from_git_xy(x: char, y: char) -> Option<Self> {
+        let code = if x == ' ' { y } else { x };
+        match code {
+            'A' => Some(FileStatus::Added),
+            'M' | 'T' => Some(FileStatus::Modified),
+            'D' => Some(FileStatus::Deleted),
+            'R' => Some(FileStatus::Renamed),
+            'C' => Some(FileStatus::Copied),
+            _ => None,
+        }
+    }
+}
+
+// Lowercase letters are accepted too, since some wrappers lowercase them
 fn file_status(s: &mut &str) -> Result<FileStatus> {
     alt((
-        'A'.map(|_| FileStatus::Added),
-        'R'.map(|_| FileStatus::Removed),
-        'M'.map(|_| FileStatus::Modified),
+        one_of(['A', 'a']).map(|_| FileStatus::Added),
+        one_of(['D', 'd']).map(|_| FileStatus::Deleted),
+        one_of(['M', 'm']).map(|_| FileStatus::Modified),
+        one_of(['R', 'r']).map(|_| FileStatus::Renamed),
+        one_of(['C', 'c']).map(|_| FileStatus::Copied),
     ))
     .parse_next(s)
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+impl FromStr for FileStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        file_status.parse(s).map_err(Error::from_parse)
+    }
+}
+
+// Serialized as the same single-letter symbol jj prints
+impl Serialize for FileStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FileStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let symbol = String::deserialize(deserializer)?;
+        symbol.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct WorkingCopyChange {
     status: FileStatus,
+    from_path: Option<PathBuf>,
     path: PathBuf,
 }
 
+impl WorkingCopyChange {
+    /// Build a change by hand. `from_path` is the old path of a rename or copy
+    pub fn new(status: FileStatus, from_path: Option<PathBuf>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            status,
+            from_path,
+            path: path.into(),
+        }
+    }
+
+    /// ```
+    /// use jj_status_parser::{FileStatus, Status};
+    /// use std::path::Path;
+    /// use std::str::FromStr;
+    ///
+    /// let status = Status::from_str(
+    ///     "Working copy changes:
+    /// A src/lib.rs
+    /// Working copy : qnxonnkx 60be3879 main | (no description set)
+    /// Parent commit: zzzzzzzz 00000000 (empty) (no description set)",
+    /// )
+    /// .unwrap();
+    /// let change = &status.file_changes()[0];
+    /// assert!(matches!(change.status(), FileStatus::Added));
+    /// assert_eq!(Path::new("src/lib.rs"), change.path());
+    /// ```
+    pub fn status(&self) -> &FileStatus {
+        &self.status
+    }
+
+    /// The path before the change, for renames
+    pub fn from_path(&self) -> Option<&Path> {
+        self.from_path.as_deref()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // `@/src/lib.rs` -> `src/lib.rs`
+    fn strip_root_prefix(&mut self) {
+        fn strip(path: &Path) -> PathBuf {
+            path.strip_prefix("@").unwrap_or(path).to_path_buf()
+        }
+        self.path = strip(&self.path);
+        self.from_path = self.from_path.as_deref().map(strip);
+    }
+
+    /// Extension of `path()`, e.g. `gz` for `foo.tar.gz`. Dotfiles like `.gitignore` have none
+    pub fn extension(&self) -> Option<&str> {
+        self.path
+            .extension()
+            .and_then(|extension| extension.to_str())
+    }
+
+    /// The change as a line of `git status --porcelain`, e.g. ` M src/lib.rs` or
+    /// `R  old.rs -> new.rs`. Unlike `FileStatus::to_git_xy`, additions, modifications and
+    /// deletions go in the worktree column, as unstaged changes. Git only reports renames and
+    /// copies in the index column, so those stay there
+    pub fn to_git_porcelain(&self) -> String {
+        let (x, y) = match self.status {
+            FileStatus::Added => (' ', 'A'),
+            FileStatus::Modified => (' ', 'M'),
+            FileStatus::Deleted => (' ', 'D'),
+            FileStatus::Renamed | FileStatus::Copied => self.status.to_git_xy(),
+        };
+        match &self.from_path {
+            Some(from_path) => format!(
+                "{x}{y} {} -> {}",
+                git_quote(from_path),
+                git_quote(&self.path)
+            ),
+            None => format!("{x}{y} {}", git_quote(&self.path)),
+        }
+    }
+}
+
+// Git quotes paths with whitespace, quotes or backslashes like a C string
+fn git_quote(path: &Path) -> Cow<'_, str> {
+    let path = path.to_string_lossy();
+    if !path.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        return path;
+    }
+    let mut quoted = String::with_capacity(path.len() + 2);
+    quoted.push('"');
+    for c in path.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+// Ordered by path, then status
+impl Ord for WorkingCopyChange {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.path
+            .cmp(&other.path)
+            .then_with(|| self.status.cmp(&other.status))
+            .then_with(|| self.from_path.cmp(&other.from_path))
+    }
+}
+
+impl PartialOrd for WorkingCopyChange {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Display for WorkingCopyChange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.status, self.path.display())
+        match &self.from_path {
+            Some(from_path) => write!(
+                f,
+                "{} {} => {}",
+                self.status,
+                from_path.display(),
+                self.path.display()
+            ),
+            None => write!(f, "{} {}", self.status, self.path.display()),
+        }
+    }
+}
+
+/// A `WorkingCopyChange` borrowing its paths from the parsed input. Quoted paths containing
+/// escapes are the only ones copied
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WorkingCopyChangeRef<'a> {
+    status: FileStatus,
+    from_path: Option<Cow<'a, str>>,
+    path: Cow<'a, str>,
+}
+
+impl<'a> WorkingCopyChangeRef<'a> {
+    pub fn status(&self) -> &FileStatus {
+        &self.status
+    }
+
+    /// The path before the change, for renames
+    pub fn from_path(&self) -> Option<&str> {
+        self.from_path.as_deref()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn to_owned(&self) -> WorkingCopyChange {
+        WorkingCopyChange {
+            status: self.status,
+            from_path: self.from_path.as_deref().map(PathBuf::from),
+            path: PathBuf::from(&*self.path),
+        }
     }
 }
 
-fn part<'a>(s: &mut &'a str) -> Result<&'a str> {
-    take_till(1.., |c: char| c == '/' || c == '\n').parse_next(s)
+fn is_line_ending(c: char) -> bool {
+    c == '\r' || c == '\n'
+}
+
+// `PathBuf` accepts `/` separators as is, so the whole line is the path
+fn path<'a>(s: &mut &'a str) -> Result<Cow<'a, str>> {
+    take_till(1.., is_line_ending)
+        .map(Cow::Borrowed)
+        .parse_next(s)
+}
+
+// jj quotes paths containing spaces, escaping any embedded quotes. Only paths with escapes
+// need to be copied
+fn quoted_path<'a>(s: &mut &'a str) -> Result<Cow<'a, str>> {
+    delimited(
+        '"',
+        alt((
+            terminated(
+                take_till(0.., |c: char| c == '"' || c == '\\' || is_line_ending(c)),
+                peek('"'),
+            )
+            .map(Cow::Borrowed),
+            escaped(
+                take_till(1.., |c: char| c == '"' || c == '\\' || is_line_ending(c)),
+                '\\',
+                alt(("\"".value("\""), "\\".value("\\"))),
+            )
+            .map(|path: String| Cow::Owned(path)),
+        )),
+        '"',
+    )
+    .parse_next(s)
 }
 
-fn path(s: &mut &str) -> Result<PathBuf> {
-    let parts: Vec<&str> = separated(1.., part, "/").parse_next(s)?;
-    let path: PathBuf = parts.iter().collect();
-    Ok(path)
+fn file_path<'a>(s: &mut &'a str) -> Result<Cow<'a, str>> {
+    alt((quoted_path, path)).parse_next(s)
 }
 
-fn file_change(s: &mut &str) -> Result<WorkingCopyChange> {
-    seq! {WorkingCopyChange {
-        status: file_status,
-        _: space1,
-        path: path
-    }}
+fn rename_paths<'a>(s: &mut &'a str) -> Result<(Cow<'a, str>, Cow<'a, str>)> {
+    separated_pair(
+        take_until(1.., " => ").and_then(terminated(file_path, eof)),
+        " => ",
+        file_path,
+    )
     .parse_next(s)
 }
 
-fn file_no_changes(s: &mut &str) -> Result<Vec<WorkingCopyChange>> {
-    let _ = "The working copy has no changes.".parse_next(s)?;
+fn file_change_ref<'a>(s: &mut &'a str) -> Result<WorkingCopyChangeRef<'a>> {
+    let status = file_status.parse_next(s)?;
+    let _ = space1.parse_next(s)?;
+    let (from_path, path) = match status {
+        FileStatus::Renamed | FileStatus::Copied => {
+            // Only look for the ` => ` separator within the current line
+            take_till(1.., is_line_ending)
+                .and_then(rename_paths)
+                .map(|(from_path, path)| (Some(from_path), path))
+                .parse_next(s)?
+        }
+        _ => (None, file_path.parse_next(s)?),
+    };
+    Ok(WorkingCopyChangeRef {
+        status,
+        from_path,
+        path,
+    })
+}
+
+// The wording differs between jj versions
+fn file_no_changes<'a>(s: &mut &'a str) -> Result<Vec<WorkingCopyChangeRef<'a>>> {
+    let _ = (
+        "The working copy ",
+        alt(("has no changes", "is clean")),
+        opt('.'),
+        space0,
+    )
+        .parse_next(s)?;
     Ok(Vec::new())
 }
 
-fn file_yes_changes(s: &mut &str) -> Result<Vec<WorkingCopyChange>> {
-    let _ = opt("Working copy changes:\n").parse_next(s)?; // TODO: I actually don't think this should be optional
-    separated(0.., file_change, "\n").parse_next(s)
+// The header is optional so the bare change lines of `jj diff --summary` parse too. Without it,
+// a line is only a change if it doesn't start the next section, e.g. a custom `M wc` label
+fn file_yes_changes<'i>(
+    options: &ParseOptions,
+) -> impl FnMut(&mut &'i str) -> Result<Vec<WorkingCopyChangeRef<'i>>> + '_ {
+    move |s: &mut &'i str| {
+        let _ = opt(("Working copy changes:", space0, line_ending)).parse_next(s)?;
+        separated(
+            0..,
+            preceded(
+                peek(take_till(0.., is_line_ending))
+                    .verify(|line: &str| !is_section_start(options, line)),
+                file_change_ref,
+            ),
+            line_ending,
+        )
+        .parse_next(s)
+    }
+}
+
+fn file_changes_ref<'i>(
+    options: &ParseOptions,
+) -> impl FnMut(&mut &'i str) -> Result<Vec<WorkingCopyChangeRef<'i>>> + '_ {
+    move |s: &mut &'i str| alt((file_no_changes, file_yes_changes(options))).parse_next(s)
 }
 
-fn file_changes(s: &mut &str) -> Result<Vec<WorkingCopyChange>> {
-    alt((file_no_changes, file_yes_changes)).parse_next(s)
+// e.g. `? notes.txt`, listed when jj's snapshot settings don't auto-track new files
+fn untracked_paths<'a>(s: &mut &'a str) -> Result<Vec<Cow<'a, str>>> {
+    let _ = ("Untracked paths:", space0, line_ending).parse_next(s)?;
+    separated(1.., preceded("? ", file_path), line_ending).parse_next(s)
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct CommitDetails {
     change_id: String,
-    commit_id: String,
+    divergent: bool,
+    commit_id: Option<String>,
     empty: bool,
-    bookmark: Option<String>,
+    conflict: bool,
+    hidden: bool,
+    bookmarks: Vec<Bookmark>,
     description: Option<String>,
+    timestamp: Option<String>,
+    trailing: Option<String>,
+    workspace: Option<String>,
 }
 
 impl CommitDetails {
+    /// Build a commit by hand, e.g. as an expected value in tests. The remaining flags are unset
+    ///
+    /// ```
+    /// use jj_status_parser::{Bookmark, CommitDetails};
+    /// use std::str::FromStr;
+    ///
+    /// let details = CommitDetails::new(
+    ///     "qnxonnkx",
+    ///     "60be3879",
+    ///     false,
+    ///     vec![Bookmark::from_str("main").unwrap()],
+    ///     Some("add parser".to_string()),
+    /// );
+    /// assert_eq!("qnxonnkx 60be3879 main | add parser", details.to_string());
+    /// ```
+    pub fn new(
+        change_id: impl Into<String>,
+        commit_id: impl Into<String>,
+        empty: bool,
+        bookmarks: Vec<Bookmark>,
+        description: Option<String>,
+    ) -> Self {
+        Self {
+            change_id: change_id.into(),
+            divergent: false,
+            commit_id: Some(commit_id.into()),
+            empty,
+            conflict: false,
+            hidden: false,
+            bookmarks,
+            description,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        }
+    }
+
     pub fn change_id(&self) -> &str {
-        &self.change_id.as_str()
+        self.change_id.as_str()
+    }
+
+    /// `None` when jj leaves the commit id out of the line
+    pub fn commit_id(&self) -> Option<&str> {
+        self.commit_id.as_deref()
+    }
+
+    /// At most the first `n` characters of the change id, e.g. for a prompt
+    pub fn change_id_short(&self, n: usize) -> &str {
+        truncate_chars(&self.change_id, n)
+    }
+
+    /// At most the first `n` characters of the commit id
+    pub fn commit_id_short(&self, n: usize) -> Option<&str> {
+        self.commit_id
+            .as_deref()
+            .map(|commit_id| truncate_chars(commit_id, n))
+    }
+
+    /// Whether this is jj's root commit, which has an all-`z` change id and an all-`0` commit id
+    pub fn is_root(&self) -> bool {
+        self.change_id.chars().all(|c| c == 'z')
+            && self
+                .commit_id
+                .as_deref()
+                .is_none_or(|commit_id| commit_id.chars().all(|c| c == '0'))
     }
 
-    pub fn commit_id(&self) -> &str {
-        &self.commit_id.as_str()
+    /// Whether jj marked the change id with `??` or `(divergent)` because several visible commits
+    /// share it
+    pub fn is_divergent(&self) -> bool {
+        self.divergent
     }
 
     pub fn empty(&self) -> bool {
         self.empty
     }
 
-    pub fn bookmark(&self) -> Option<&String> {
-        self.bookmark.as_ref()
+    /// Whether jj marked the commit with `(conflict)`
+    pub fn is_conflicted(&self) -> bool {
+        self.conflict
     }
 
+    /// Whether jj marked the commit with `(hidden)`, e.g. after it was abandoned
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        self.bookmarks.as_ref()
+    }
+
+    /// The description, or `EMPTY_DESCRIPTION` when there isn't one
     pub fn description(&self) -> &str {
         match &self.description {
             Some(description) => description.as_str(),
             None => EMPTY_DESCRIPTION,
         }
     }
+
+    pub fn description_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.description.as_deref().unwrap_or(default)
+    }
+
+    /// Whether the commit has a description, as opposed to jj's placeholder
+    pub fn has_description(&self) -> bool {
+        self.description.is_some()
+    }
+
+    pub fn timestamp(&self) -> Option<&str> {
+        self.timestamp.as_deref()
+    }
+
+    /// Extra text after `(no description set)` on the same line, e.g. an author added by a custom
+    /// template. After a real description there's no telling the two apart, so it stays part of
+    /// the description
+    pub fn trailing(&self) -> Option<&str> {
+        self.trailing.as_deref()
+    }
+
+    /// Whether `symbol` (a change or commit id prefix, or a bookmark) unambiguously refers to this
+    /// commit. `@` and `@-` are handled by `Commit::matches_revset_symbol`
+    pub fn matches_revset_symbol(&self, symbol: &str) -> bool {
+        if symbol.is_empty() {
+            return false;
+        }
+        if !self.divergent && self.change_id.starts_with(symbol) {
+            return true;
+        }
+        if self
+            .commit_id
+            .as_deref()
+            .is_some_and(|commit_id| commit_id.starts_with(symbol))
+        {
+            return true;
+        }
+        self.bookmarks.iter().any(|bookmark| {
+            !bookmark.conflicted
+                && match &bookmark.remote {
+                    Some(remote) => symbol == format!("{}@{remote}", bookmark.name),
+                    None => symbol == bookmark.name,
+                }
+        })
+    }
+
+    /// Name of the workspace, when jj labels the working copy with one (`Working copy  (name@) :`)
+    pub fn workspace(&self) -> Option<&str> {
+        self.workspace.as_deref()
+    }
 }
 
 impl Display for CommitDetails {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let empty = if self.empty { "(empty)" } else { "" };
-        let bookmark = match &self.bookmark {
-            Some(bookmark) => {
-                format!("{bookmark} | ")
-            }
-            None => String::new(),
+        let divergent = if self.divergent { "??" } else { "" };
+        let conflict = if self.conflict { "(conflict) " } else { "" };
+        let empty = if self.empty { "(empty) " } else { "" };
+        let hidden = if self.hidden { "(hidden) " } else { "" };
+        let bookmarks = if self.bookmarks.is_empty() {
+            String::new()
+        } else {
+            format!("{} | ", join_bookmarks(&self.bookmarks))
         };
         let description = match &self.description {
-            Some(description) => &description,
+            Some(description) => description,
             None => EMPTY_DESCRIPTION,
         };
+        let commit_id = match &self.commit_id {
+            Some(commit_id) => format!("{commit_id} "),
+            None => String::new(),
+        };
         write!(
             f,
-            "{} {} {empty}{bookmark}{description}",
-            self.change_id, self.commit_id
-        )
+            "{}{divergent} {commit_id}{conflict}{empty}{hidden}{bookmarks}{description}",
+            self.change_id
+        )?;
+        if let Some(trailing) = &self.trailing {
+            write!(f, " {trailing}")?;
+        }
+        if let Some(timestamp) = &self.timestamp {
+            write!(f, " {timestamp}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A `CommitDetails` borrowing from the parsed input, see `parse_ref`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommitDetailsRef<'a> {
+    change_id: &'a str,
+    divergent: bool,
+    commit_id: Option<&'a str>,
+    empty: bool,
+    conflict: bool,
+    hidden: bool,
+    bookmarks: Vec<BookmarkRef<'a>>,
+    description: Option<&'a str>,
+    timestamp: Option<&'a str>,
+    trailing: Option<&'a str>,
+    workspace: Option<&'a str>,
+}
+
+impl<'a> CommitDetailsRef<'a> {
+    pub fn change_id(&self) -> &'a str {
+        self.change_id
+    }
+
+    pub fn commit_id(&self) -> Option<&'a str> {
+        self.commit_id
+    }
+
+    pub fn is_divergent(&self) -> bool {
+        self.divergent
+    }
+
+    pub fn empty(&self) -> bool {
+        self.empty
+    }
+
+    pub fn is_conflicted(&self) -> bool {
+        self.conflict
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn bookmarks(&self) -> &[BookmarkRef<'a>] {
+        &self.bookmarks
+    }
+
+    /// The description as printed, without normalising line endings. `None` for the placeholder
+    pub fn description(&self) -> Option<&'a str> {
+        self.description
+    }
+
+    pub fn timestamp(&self) -> Option<&'a str> {
+        self.timestamp
+    }
+
+    pub fn trailing(&self) -> Option<&'a str> {
+        self.trailing
+    }
+
+    pub fn workspace(&self) -> Option<&'a str> {
+        self.workspace
+    }
+
+    pub fn to_owned(&self) -> CommitDetails {
+        self.to_owned_with(&ParseOptions::default())
+    }
+
+    fn to_owned_with(&self, options: &ParseOptions) -> CommitDetails {
+        CommitDetails {
+            change_id: self.change_id.to_string(),
+            divergent: self.divergent,
+            commit_id: self.commit_id.map(String::from),
+            empty: self.empty,
+            conflict: self.conflict,
+            hidden: self.hidden,
+            bookmarks: self
+                .bookmarks
+                .iter()
+                .copied()
+                .map(BookmarkRef::to_owned)
+                .collect(),
+            description: self
+                .description
+                .map(|description| owned_description(options, description)),
+            timestamp: self.timestamp.map(String::from),
+            trailing: self.trailing.map(String::from),
+            workspace: self.workspace.map(String::from),
+        }
+    }
+}
+
+// Slicing at a char boundary rather than byte `n`
+fn truncate_chars(s: &str, n: usize) -> &str {
+    match s.char_indices().nth(n) {
+        Some((end, _)) => &s[..end],
+        None => s,
     }
 }
 
@@ -144,511 +813,4007 @@ fn char_between_inclusive(c: char, lower: char, upper: char) -> bool {
     c >= lower && c <= upper
 }
 
-fn change_id(s: &mut &str) -> Result<String> {
-    take_while(1.., |c: char| char_between_inclusive(c, 'k', 'z'))
-        .map(|s: &str| s.to_string())
-        .parse_next(s)
+// jj writes change ids in "reverse hex", `z` to `k` standing for 0 to f. Any other letter or digit
+// running on from the id is an error rather than the end of the id
+fn change_id<'a>(s: &mut &'a str) -> Result<&'a str> {
+    terminated(
+        take_while(1.., |c: char| char_between_inclusive(c, 'k', 'z')),
+        not(one_of(|c: char| c.is_alphanumeric())),
+    )
+    .parse_next(s)
 }
 
-fn commit_id(s: &mut &str) -> Result<String> {
+fn commit_id<'a>(s: &mut &'a str) -> Result<&'a str> {
     take_while(1.., |c: char| {
         char_between_inclusive(c, '0', '9') || char_between_inclusive(c, 'a', 'f')
     })
-    .map(|s: &str| s.to_string())
     .parse_next(s)
 }
 
-use winnow::combinator::peek;
-fn bookmark(s: &mut &str) -> Result<String> {
-    let bookmark = peek(take_until(1.., " |").map(|x: &str| x.to_string())).parse_next(s)?;
-    if bookmark.contains("\n") {
-        // Without this peek check, the bookmark would capture all the way to the next line's bookmark
-        return Err(ContextError::new());
-    }
-    let bookmark = take_until(1.., " |")
-        .map(|x: &str| x.to_string())
-        .parse_next(s)?;
-
-    let _ = " |".parse_next(s)?;
-    Ok(bookmark)
-}
-
-fn description(s: &mut &str) -> Result<Option<String>> {
+// Some configs leave the commit id out, so a hex word shorter than jj's default 8 digits is only a
+// commit id when an annotation, the placeholder, bookmarks or the end of the line follow it. Else
+// it's the start of the description, like `add` in `add parser`
+fn commit_id_field<'a>(s: &mut &'a str) -> Result<&'a str> {
+    let field_follows = preceded(
+        field_separator,
+        alt((
+            alt((
+                "(empty)",
+                "(conflict)",
+                "(divergent)",
+                "(hidden)",
+                EMPTY_DESCRIPTION,
+            ))
+            .void(),
+            bookmark_column.void(),
+        )),
+    );
     alt((
-        "(no description set)".map(|_| None),
-        alt((take_till(1.., |c: char| c == '\n'), rest)).map(|s: &str| Some(s.to_string())),
+        terminated(
+            commit_id.verify(|commit_id: &str| commit_id.len() >= 8),
+            peek(alt((space1, line_ending, eof))),
+        ),
+        terminated(
+            commit_id,
+            peek(alt((line_ending.void(), eof.void(), field_follows))),
+        ),
     ))
     .parse_next(s)
 }
 
-fn empty(s: &mut &str) -> Result<bool> {
-    opt("(empty) ")
-        .map(|x| match x {
-            Some(_) => true,
-            None => false,
-        })
-        .parse_next(s)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Bookmark {
+    name: String,
+    remote: Option<String>,
+    conflicted: bool,
+    out_of_sync: bool,
+    ahead: Option<usize>,
+    behind: Option<usize>,
 }
 
-fn commit_details(s: &mut &str) -> Result<CommitDetails> {
-    seq! {CommitDetails {
-        change_id: change_id,
-        _: space1,
-        commit_id: commit_id,
-        _: space1,
-        empty: empty,
-        _: space0,
-        bookmark: opt(bookmark),
-        _: space0,
-        description: description,
-    }}
-    .parse_next(s)
-}
+impl Bookmark {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
-pub struct Status {
-    file_changes: Vec<WorkingCopyChange>,
-    working_copy: Commit,
-    parent_commit: Commit,
-}
+    pub fn remote(&self) -> Option<&str> {
+        self.remote.as_deref()
+    }
 
-impl Status {
-    pub fn file_changes(&self) -> &[WorkingCopyChange] {
-        &self.file_changes.as_ref()
+    /// Whether jj marked the bookmark with `??` because it points at several commits
+    pub fn is_conflicted(&self) -> bool {
+        self.conflicted
     }
 
-    pub fn working_copy(&self) -> &Commit {
-        &self.working_copy
+    /// Whether jj marked the bookmark with `*` because it differs from its tracked remote
+    pub fn is_out_of_sync(&self) -> bool {
+        self.out_of_sync
     }
 
-    pub fn parent_commit(&self) -> &Commit {
-        &self.parent_commit
+    /// Number of commits the bookmark is ahead of its tracked remote, if jj reported it
+    pub fn ahead(&self) -> Option<usize> {
+        self.ahead
     }
-}
 
-fn working_copy(s: &mut &str) -> Result<Commit> {
-    let _ = "Working copy".parse_next(s)?;
-    let _ = space1.parse_next(s)?;
-    let _ = ":".parse_next(s)?;
-    let _ = space1.parse_next(s)?;
-    commit_details
-        .map(|details| Commit::WorkingCopy(details))
-        .parse_next(s)
+    /// Number of commits the bookmark is behind its tracked remote, if jj reported it
+    pub fn behind(&self) -> Option<usize> {
+        self.behind
+    }
 }
 
-fn parent_commit(s: &mut &str) -> Result<Commit> {
-    let _ = "Parent commit:".parse_next(s)?;
-    let _ = space1.parse_next(s)?;
-    commit_details
-        .map(|details| Commit::ParentCommit(details))
-        .parse_next(s)
+/// A `Bookmark` borrowing from the parsed input, see `CommitDetailsRef::bookmarks`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BookmarkRef<'a> {
+    name: &'a str,
+    remote: Option<&'a str>,
+    conflicted: bool,
+    out_of_sync: bool,
+    ahead: Option<usize>,
+    behind: Option<usize>,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
-#[serde(tag = "change_type")]
-pub enum Commit {
-    WorkingCopy(CommitDetails),
-    ParentCommit(CommitDetails),
-}
+impl<'a> BookmarkRef<'a> {
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
 
-impl Commit {
-    pub fn change_id(&self) -> &str {
-        match self {
-            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.change_id(),
-        }
+    pub fn remote(&self) -> Option<&'a str> {
+        self.remote
     }
 
-    pub fn commit_id(&self) -> &str {
-        match self {
-            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.commit_id(),
-        }
+    pub fn is_conflicted(&self) -> bool {
+        self.conflicted
     }
 
-    pub fn empty(&self) -> bool {
-        match self {
-            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.empty(),
-        }
+    pub fn is_out_of_sync(&self) -> bool {
+        self.out_of_sync
     }
 
-    pub fn bookmark(&self) -> Option<&String> {
-        match self {
-            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.bookmark(),
-        }
+    pub fn ahead(&self) -> Option<usize> {
+        self.ahead
     }
 
-    pub fn description(&self) -> &str {
-        match self {
-            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.description(),
-        }
+    pub fn behind(&self) -> Option<usize> {
+        self.behind
     }
-}
 
-impl Display for Commit {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::WorkingCopy(details) => {
-                write!(f, "{details}")
-            }
-            Self::ParentCommit(details) => {
-                write!(f, "{details}")
-            }
+    pub fn to_owned(self) -> Bookmark {
+        Bookmark {
+            name: self.name.to_string(),
+            remote: self.remote.map(String::from),
+            conflicted: self.conflicted,
+            out_of_sync: self.out_of_sync,
+            ahead: self.ahead,
+            behind: self.behind,
         }
     }
 }
 
-fn status(s: &mut &str) -> Result<Status> {
-    seq! {Status {
-        file_changes: file_changes,
-        _: opt(newline),
-        working_copy: working_copy,
-        _: newline,
-        parent_commit: parent_commit,
-    }}
+fn commits(n: usize) -> &'static str {
+    if n == 1 { "commit" } else { "commits" }
+}
+
+impl Display for Bookmark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.conflicted {
+            write!(f, "??")?;
+        }
+        if self.out_of_sync {
+            write!(f, "*")?;
+        }
+        if let Some(remote) = &self.remote {
+            write!(f, "@{remote}")?;
+        }
+        match (self.ahead, self.behind) {
+            (Some(ahead), Some(behind)) => write!(f, " (+{ahead}/-{behind})"),
+            (Some(ahead), None) => write!(f, " (ahead by {ahead} {})", commits(ahead)),
+            (None, Some(behind)) => write!(f, " (behind by {behind} {})", commits(behind)),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+// Space separated, the way jj prints several bookmarks on one commit
+fn join_bookmarks(bookmarks: &[Bookmark]) -> String {
+    bookmarks
+        .iter()
+        .map(|bookmark| bookmark.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn commit_count(s: &mut &str) -> Result<usize> {
+    terminated(dec_uint, (" commit", opt('s'))).parse_next(s)
+}
+
+// `(+N/-M)`
+fn sync_compact(s: &mut &str) -> Result<(Option<usize>, Option<usize>)> {
+    delimited("(+", separated_pair(dec_uint, "/-", dec_uint), ')')
+        .map(|(ahead, behind)| (Some(ahead), Some(behind)))
+        .parse_next(s)
+}
+
+// `(ahead by N commits)` or `(behind by N commits)`
+fn sync_verbose(s: &mut &str) -> Result<(Option<usize>, Option<usize>)> {
+    delimited(
+        '(',
+        alt((
+            preceded("ahead by ", commit_count).map(|ahead| (Some(ahead), None)),
+            preceded("behind by ", commit_count).map(|behind| (None, Some(behind))),
+        )),
+        ')',
+    )
     .parse_next(s)
 }
 
-impl FromStr for Status {
-    type Err = ParseError;
+fn bookmark_value<'a>(s: &mut &'a str) -> Result<BookmarkRef<'a>> {
+    let full_name = take_till(1.., ' ').parse_next(s)?;
+    // Remote names can't contain `@`, so the last one starts the remote and anything before it,
+    // slashes and dots included, is the name
+    let (name, remote) = match full_name.rsplit_once('@') {
+        Some((name, remote)) => (name, Some(remote)),
+        None => (full_name, None),
+    };
+    let (name, out_of_sync) = match name.strip_suffix('*') {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    let (name, conflicted) = match name.strip_suffix("??") {
+        Some(name) => (name, true),
+        None => (name, false),
+    };
+    let (ahead, behind) = opt(preceded(space1, alt((sync_compact, sync_verbose))))
+        .parse_next(s)?
+        .unwrap_or_default();
+    Ok(BookmarkRef {
+        name,
+        remote,
+        conflicted,
+        out_of_sync,
+        ahead,
+        behind,
+    })
+}
+
+impl FromStr for Bookmark {
+    type Err = Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        status.parse(s).map_err(|e| ParseError::from_parse(e))
+        bookmark_value
+            .map(BookmarkRef::to_owned)
+            .parse(s)
+            .map_err(Error::from_parse)
     }
 }
 
-impl Display for Status {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+fn bookmark_list<'a>(s: &mut &'a str) -> Result<Vec<BookmarkRef<'a>>> {
+    separated(1.., bookmark_value, space1).parse_next(s)
+}
+
+// The bookmarks before ` | `
+fn bookmark_column<'a>(s: &mut &'a str) -> Result<Vec<BookmarkRef<'a>>> {
+    let bookmark = peek(take_until(1.., " |")).parse_next(s)?;
+    if bookmark.contains("\n") {
+        // Without this peek check, the bookmark would capture all the way to the next line's bookmark
+        return Err(ContextError::new());
+    }
+    let bookmarks = take_until(1.., " |")
+        .and_then(terminated(bookmark_list, eof))
+        .parse_next(s)?;
+
+    let _ = " |".parse_next(s)?;
+    Ok(bookmarks)
+}
+
+fn two_digits<'a>(s: &mut &'a str) -> Result<&'a str> {
+    take_while(2, AsChar::is_dec_digit).parse_next(s)
+}
+
+// RFC3339, e.g. `2024-01-02T10:00:00Z` or `2024-01-02T10:00:00.123+01:00`
+fn timestamp<'a>(s: &mut &'a str) -> Result<&'a str> {
+    (
+        take_while(4, AsChar::is_dec_digit),
+        '-',
+        two_digits,
+        '-',
+        two_digits,
+        'T',
+        two_digits,
+        ':',
+        two_digits,
+        ':',
+        two_digits,
+        opt(('.', digit1)),
+        alt((
+            "Z".void(),
+            (one_of(['+', '-']), two_digits, ':', two_digits).void(),
+        )),
+    )
+        .take()
+        .parse_next(s)
+}
+
+// An indented line continuing a wrapped description. Anything else after the commit line, like
+// the next command's output, isn't part of the status
+fn indented_line<'a>(s: &mut &'a str) -> Result<&'a str> {
+    take_till(1.., is_line_ending)
+        .verify(|line: &str| line.starts_with([' ', '\t']))
+        .parse_next(s)
+}
+
+// The rest of the line plus any indented lines after it
+fn description_text<'a>(s: &mut &'a str) -> Result<&'a str> {
+    let text = peek(
+        (
+            take_till(0.., is_line_ending),
+            repeat(0.., (line_ending, indented_line)).map(|()| ()),
+        )
+            .take(),
+    )
+    .parse_next(s)?;
+    Ok(s.next_slice(len_before_timestamp(text)))
+}
+
+// Length of `text` without a final timestamp column, leaving it for the `timestamp` parser
+fn len_before_timestamp(text: &str) -> usize {
+    if timestamp.parse(text).is_ok() {
+        return 0;
+    }
+    match text.rsplit_once(' ') {
+        Some((text, last)) if timestamp.parse(last).is_ok() => text.len(),
+        _ => text.len(),
+    }
+}
+
+// Whatever a custom template prints after the description placeholder, up to any timestamp
+fn trailing_text<'a>(s: &mut &'a str) -> Result<&'a str> {
+    let line = peek(take_till(1.., is_line_ending)).parse_next(s)?;
+    match len_before_timestamp(line) {
+        0 => Err(ContextError::new()),
+        len => Ok(s.next_slice(len)),
+    }
+}
+
+fn description<'a>(s: &mut &'a str) -> Result<Option<&'a str>> {
+    alt((
+        "(no description set)".map(|_| None),
+        // Nothing but whitespace after the ids is treated the same as the placeholder
+        description_text.map(|s: &str| (!s.trim().is_empty()).then_some(s)),
+    ))
+    .parse_next(s)
+}
+
+// Line endings are normalised, and continuation lines joined if `unwrap_descriptions` is set
+fn owned_description(options: &ParseOptions, description: &str) -> String {
+    let description = description.replace("\r\n", "\n");
+    if options.unwrap_descriptions {
+        unwrap_lines(&description)
+    } else {
+        description
+    }
+}
+
+// Join lines that start with whitespace onto the previous line
+fn unwrap_lines(s: &str) -> String {
+    let mut unwrapped = String::with_capacity(s.len());
+    for (i, line) in s.split('\n').enumerate() {
+        let trimmed = line.trim_start();
+        if i == 0 {
+            unwrapped.push_str(line);
+        } else if trimmed.len() < line.len() {
+            unwrapped.push(' ');
+            unwrapped.push_str(trimmed);
+        } else {
+            unwrapped.push('\n');
+            unwrapped.push_str(line);
+        }
+    }
+    unwrapped
+}
+
+// `(empty)`, `(conflict)`, `(divergent)` and `(hidden)`, in any order
+#[derive(Default)]
+struct Annotations {
+    empty: bool,
+    conflict: bool,
+    divergent: bool,
+    hidden: bool,
+}
+
+// jj separates the fields of a commit line with a single space
+fn field_separator(s: &mut &str) -> Result<char> {
+    one_of([' ', '\t']).parse_next(s)
+}
+
+fn annotations(s: &mut &str) -> Result<Annotations> {
+    let found: Vec<&str> = repeat(
+        0..=4,
+        terminated(
+            alt(("(empty)", "(conflict)", "(divergent)", "(hidden)")),
+            opt(field_separator),
+        ),
+    )
+    .parse_next(s)?;
+    let mut annotations = Annotations::default();
+    for annotation in found {
+        match annotation {
+            "(empty)" => annotations.empty = true,
+            "(conflict)" => annotations.conflict = true,
+            "(divergent)" => annotations.divergent = true,
+            _ => annotations.hidden = true,
+        }
+    }
+    Ok(annotations)
+}
+
+fn commit_details_ref<'i>(s: &mut &'i str) -> Result<CommitDetailsRef<'i>> {
+    let change_id = change_id
+        .context(StrContext::Expected(StrContextValue::Description(
+            "change id of the letters `k` to `z`",
+        )))
+        .parse_next(s)?;
+    let divergent = opt("??").map(|x| x.is_some()).parse_next(s)?;
+    let _ = field_separator.parse_next(s)?;
+    let commit_id = opt(commit_id_field).parse_next(s)?;
+    let _ = opt(field_separator).parse_next(s)?;
+    let annotations = annotations.parse_next(s)?;
+    // Only the one separator after ` |`, so a description's own leading whitespace is kept
+    // jj always prints bookmarks with a ` |` after them, so without one a lone word such as
+    // `(empty) feature` is a one-word description
+    let bookmarks = opt(terminated(bookmark_column, opt(field_separator)))
+        .parse_next(s)?
+        .unwrap_or_default();
+    let description = description.parse_next(s)?;
+    let trailing = opt(preceded(space1, trailing_text)).parse_next(s)?;
+    let timestamp = opt(preceded(space0, timestamp)).parse_next(s)?;
+    Ok(CommitDetailsRef {
+        change_id,
+        divergent: divergent || annotations.divergent,
+        commit_id,
+        empty: annotations.empty,
+        conflict: annotations.conflict,
+        hidden: annotations.hidden,
+        bookmarks,
+        description,
+        timestamp,
+        trailing,
+        workspace: None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Status {
+    file_changes: Vec<WorkingCopyChange>,
+    #[serde(default)]
+    untracked: Vec<PathBuf>,
+    working_copy: Option<Commit>,
+    #[serde(default, alias = "parent_commit", deserialize_with = "one_or_many")]
+    parent_commits: Vec<Commit>,
+    hints: Vec<String>,
+}
+
+// Older versions serialized a single, possibly null, `parent_commit`
+fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<Commit>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        Many(Vec<Commit>),
+        One(Option<Commit>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(commits) => commits,
+        OneOrMany::One(commit) => commit.into_iter().collect(),
+    })
+}
+
+impl Status {
+    /// Assemble a status by hand, e.g. as an expected value in tests
+    pub fn builder() -> StatusBuilder {
+        StatusBuilder::default()
+    }
+
+    pub fn file_changes(&self) -> &[WorkingCopyChange] {
+        self.file_changes.as_ref()
+    }
+
+    /// Paths listed under `Untracked paths:`, which jj shows when they aren't auto-tracked
+    pub fn untracked(&self) -> &[PathBuf] {
+        &self.untracked
+    }
+
+    /// Changes grouped by status (see `FileStatus` for the order), then sorted by path
+    pub fn sorted_changes(&self) -> Vec<&WorkingCopyChange> {
+        let mut changes: Vec<&WorkingCopyChange> = self.file_changes.iter().collect();
+        changes.sort_by(|a, b| a.status.cmp(&b.status).then_with(|| a.cmp(b)));
+        changes
+    }
+
+    /// Consume the status, keeping only the file changes
+    pub fn into_file_changes(self) -> Vec<WorkingCopyChange> {
+        self.file_changes
+    }
+
+    /// The working copy, unless jj left it out of the status
+    pub fn working_copy(&self) -> Option<&Commit> {
+        self.working_copy.as_ref()
+    }
+
+    /// The first parent commit. `None` when jj was configured to hide it, or printed no parent
+    pub fn parent_commit(&self) -> Option<&Commit> {
+        self.parent_commits.first()
+    }
+
+    /// All parent commits in order, e.g. both sides of a merge. May be empty
+    pub fn parent_commits(&self) -> &[Commit] {
+        &self.parent_commits
+    }
+
+    fn commits(&self) -> impl Iterator<Item = &Commit> {
+        self.working_copy.iter().chain(&self.parent_commits)
+    }
+
+    fn bookmarks(&self) -> impl Iterator<Item = &Bookmark> {
+        self.commits().flat_map(|commit| commit.bookmarks())
+    }
+
+    /// Commit ids of the working copy, then each parent in order, skipping any that jj left out
+    pub fn commit_ids(&self) -> Vec<&str> {
+        self.commits()
+            .filter_map(|commit| commit.commit_id())
+            .collect()
+    }
+
+    /// Change ids of the working copy, then each parent in order
+    pub fn change_ids(&self) -> Vec<&str> {
+        self.commits().map(|commit| commit.change_id()).collect()
+    }
+
+    /// Bookmarks on the working copy and parent that track a remote, e.g. `main@origin`
+    pub fn remote_bookmarks(&self) -> Vec<&Bookmark> {
+        self.bookmarks()
+            .filter(|bookmark| bookmark.remote().is_some())
+            .collect()
+    }
+
+    /// Bookmarks on the working copy and parent without a remote
+    pub fn local_bookmarks(&self) -> Vec<&Bookmark> {
+        self.bookmarks()
+            .filter(|bookmark| bookmark.remote().is_none())
+            .collect()
+    }
+
+    /// Whether the working copy has no file changes, i.e. `The working copy has no changes.`, and
+    /// isn't marked `(conflict)`
+    pub fn is_clean(&self) -> bool {
+        self.file_changes.is_empty()
+            && !self
+                .working_copy
+                .as_ref()
+                .is_some_and(Commit::is_conflicted)
+    }
+
+    /// Whether there is anything to commit, like git's "nothing to commit" check. jj's working copy
+    /// is itself a commit, so besides file changes this counts a working copy that isn't empty or
+    /// already has a description. A fresh `jj new` has nothing to commit
+    pub fn anything_to_commit(&self) -> bool {
+        !self.file_changes.is_empty()
+            || self
+                .working_copy
+                .as_ref()
+                .is_some_and(|working_copy| !working_copy.empty() || working_copy.has_description())
+    }
+
+    /// Overall state of the working copy, for prompts and summaries
+    pub fn state(&self) -> RepoState {
+        if self
+            .hints
+            .iter()
+            .any(|hint| hint.contains("There are unresolved conflicts"))
+            || self
+                .working_copy
+                .as_ref()
+                .is_some_and(Commit::is_conflicted)
+        {
+            RepoState::Conflicted
+        } else if self.is_clean() && self.parent_commit().is_some_and(Commit::is_root) {
+            RepoState::Fresh
+        } else if self.is_clean() {
+            RepoState::Clean
+        } else {
+            RepoState::Dirty
+        }
+    }
+
+    /// Suggested prompt color for `state()`: green, yellow, red, or blue
+    pub fn prompt_color_hint(&self) -> &'static str {
+        match self.state() {
+            RepoState::Clean => "green",
+            RepoState::Dirty => "yellow",
+            RepoState::Conflicted => "red",
+            RepoState::Fresh => "blue",
+        }
+    }
+
+    pub fn working_copy_description_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.working_copy
+            .as_ref()
+            .map_or(default, |commit| commit.description_or(default))
+    }
+
+    pub fn parent_description_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.parent_commit()
+            .map_or(default, |commit| commit.description_or(default))
+    }
+
+    /// `Hint:` and `Warning:` blocks jj interleaved with the status, in order of appearance
+    pub fn hints(&self) -> &[String] {
+        self.hints.as_ref()
+    }
+
+    /// Changes whose path is inside `prefix`, compared component-wise
+    pub fn changes_under(&self, prefix: &Path) -> Vec<&WorkingCopyChange> {
+        self.file_changes
+            .iter()
+            .filter(|change| change.path.starts_with(prefix))
+            .collect()
+    }
+
+    /// Paths of all changed files. Renames yield their destination
+    ///
+    /// ```
+    /// use jj_status_parser::Status;
+    /// use std::path::Path;
+    /// use std::str::FromStr;
+    ///
+    /// let status = Status::from_str(
+    ///     "Working copy changes:
+    /// A README.md
+    /// A src/main.rs
+    /// Working copy : qnxonnkx 60be3879 main | (no description set)
+    /// Parent commit: zzzzzzzz 00000000 (empty) (no description set)",
+    /// )
+    /// .unwrap();
+    /// let paths: Vec<&Path> = status.changed_paths().collect();
+    /// assert_eq!(vec![Path::new("README.md"), Path::new("src/main.rs")], paths);
+    /// ```
+    pub fn changed_paths(&self) -> impl Iterator<Item = &Path> {
+        self.file_changes.iter().map(|change| change.path())
+    }
+
+    /// Number of distinct directories containing a changed file, counting the repo root
+    pub fn touched_dir_count(&self) -> usize {
+        self.changed_paths()
+            .map(|path| path.parent().unwrap_or(Path::new("")))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Size in bytes of each changed file under `root`. Deleted files, and files that can't be
+    /// read, are `None`
+    #[cfg(feature = "fs")]
+    pub fn changed_file_sizes(&self, root: &Path) -> Vec<(PathBuf, Option<u64>)> {
+        self.file_changes
+            .iter()
+            .map(|change| {
+                let size = match change.status {
+                    FileStatus::Deleted => None,
+                    _ => std::fs::metadata(root.join(&change.path))
+                        .ok()
+                        .map(|metadata| metadata.len()),
+                };
+                (change.path.clone(), size)
+            })
+            .collect()
+    }
+
+    /// Parse a status whose lines are labelled differently from the defaults
+    pub fn parse_with_options(
+        input: &str,
+        options: &ParseOptions,
+    ) -> std::result::Result<Status, Error> {
+        if input.trim().is_empty() {
+            return Err(Error::EmptyInput);
+        }
+        let (input, removed) = strip_ansi(input);
+        // jj ends its output with a newline
+        terminated(status(options), multispace0)
+            .parse(&input)
+            .map_err(|error| {
+                let mut error = Error::from_parse(error);
+                if let Error::Parse { offset, .. } = &mut error {
+                    *offset = unstripped_offset(*offset, &removed);
+                }
+                error
+            })
+    }
+
+    /// Read `reader` to the end and parse it, e.g. the stdout of a `jj status` child process
+    pub fn from_reader<R: io::Read>(mut reader: R) -> std::result::Result<Status, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(|source| Error::Io {
+            kind: source.kind(),
+            message: source.to_string(),
+        })?;
+        Status::try_from(bytes.as_slice())
+    }
+
+    /// Parse a status from the start of `input`, returning whatever follows it unconsumed.
+    /// Use `from_str` when the whole input must be a status.
+    pub fn parse_partial(input: &str) -> std::result::Result<(Status, &str), Error> {
+        (status(&ParseOptions::default()), rest)
+            .parse(input)
+            .map_err(Error::from_parse)
+    }
+
+    /// Serialize as json straight into `writer` without building an intermediate `String`
+    pub fn to_json_writer<W: io::Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Changes with the given status, e.g. only the added files
+    pub fn files_with_status(
+        &self,
+        status: FileStatus,
+    ) -> impl Iterator<Item = &WorkingCopyChange> {
+        self.file_changes
+            .iter()
+            .filter(move |change| change.status == status)
+    }
+
+    /// Number of changed files with each status
+    pub fn summary(&self) -> StatusSummary {
+        let mut summary = StatusSummary::default();
         for change in &self.file_changes {
-            write!(f, "{change}")?;
+            match change.status {
+                FileStatus::Added => summary.added += 1,
+                FileStatus::Modified => summary.modified += 1,
+                FileStatus::Deleted => summary.deleted += 1,
+                FileStatus::Renamed => summary.renamed += 1,
+                FileStatus::Copied => summary.copied += 1,
+            }
         }
-        write!(f, "Working copy : {}", self.working_copy)?;
-        write!(f, "Parent commit: {}", self.parent_commit)?;
-        Ok(())
+        summary
+    }
+
+    /// Flat string values for templating engines, keyed like `working_copy_change_id` and `parent_description`
+    pub fn to_hash_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        let commits = [
+            ("working_copy", self.working_copy.as_ref()),
+            ("parent", self.parent_commit()),
+        ];
+        for (prefix, commit) in commits {
+            let Some(commit) = commit else {
+                continue;
+            };
+            map.insert(
+                format!("{prefix}_change_id"),
+                commit.change_id().to_string(),
+            );
+            map.insert(
+                format!("{prefix}_commit_id"),
+                commit.commit_id().unwrap_or_default().to_string(),
+            );
+            map.insert(
+                format!("{prefix}_bookmarks"),
+                join_bookmarks(commit.bookmarks()),
+            );
+            map.insert(
+                format!("{prefix}_description"),
+                commit.description().to_string(),
+            );
+            map.insert(format!("{prefix}_empty"), commit.empty().to_string());
+        }
+        map.insert(String::from("clean"), self.is_clean().to_string());
+        map.insert(
+            String::from("file_count"),
+            self.file_changes.len().to_string(),
+        );
+        let summary = self.summary();
+        let counts = [
+            ("added", summary.added),
+            ("modified", summary.modified),
+            ("deleted", summary.deleted),
+            ("renamed", summary.renamed),
+            ("copied", summary.copied),
+        ];
+        for (key, count) in counts {
+            map.insert(String::from(key), count.to_string());
+        }
+        map
+    }
+
+    /// Serialize as RON. `Commit`s are written as structs with a `change_type` field, as in json
+    #[cfg(feature = "ron")]
+    pub fn to_ron_string(&self) -> std::result::Result<String, ron::Error> {
+        ron::to_string(self)
+    }
+
+    /// The full status as json, plus a `summary` object with file counts and the overall state
+    pub fn json_with_summary(&self) -> serde_json::Result<String> {
+        let summary = JsonSummary {
+            files: self.file_changes.len(),
+            counts: self.summary(),
+            state: self.state(),
+        };
+        serde_json::to_string(&StatusWithSummary {
+            status: self,
+            summary,
+        })
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use pretty_assertions::assert_eq;
-    use s_string::s;
-    use winnow::error::ContextError;
+/// Serialized in lowercase, e.g. `"conflicted"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoState {
+    /// No file changes
+    Clean,
+    /// Some files changed
+    Dirty,
+    /// jj warned about unresolved conflicts
+    Conflicted,
+    /// No file changes, directly on top of the root commit, as in a newly created repo
+    Fresh,
+}
 
-    const HEADER: &str = "Working copy changes:";
-    const FILE1: &str = "A src/lib.rs";
-    const FILE2: &str = "A src/main.rs";
-    const WORKING: &str = "Working copy : qnxonnkx 60be3879 main | (no description set)";
-    const PARENT: &str = "Parent commit: zzzzzzzz 00000000 (empty) (no description set)";
+/// Counts of changed files by status, see `Status::summary`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct StatusSummary {
+    pub added: usize,
+    pub modified: usize,
+    pub deleted: usize,
+    pub renamed: usize,
+    pub copied: usize,
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    files: usize,
+    #[serde(flatten)]
+    counts: StatusSummary,
+    state: RepoState,
+}
+
+#[derive(Serialize)]
+struct StatusWithSummary<'a> {
+    #[serde(flatten)]
+    status: &'a Status,
+    summary: JsonSummary,
+}
+
+fn label_expected(label: &str, default: &'static str) -> StrContextValue {
+    if label == default.trim_end_matches([' ', ':']) {
+        StrContextValue::StringLiteral(default)
+    } else {
+        StrContextValue::Description("custom label")
+    }
+}
+
+fn working_copy_ref<'i>(
+    options: &ParseOptions,
+) -> impl FnMut(&mut &'i str) -> Result<CommitDetailsRef<'i>> + '_ {
+    move |s: &mut &'i str| {
+        let label = options.working_copy_label();
+        let _ = label
+            .context(StrContext::Expected(label_expected(
+                label,
+                "Working copy :",
+            )))
+            .parse_next(s)?;
+        let _ = space0.parse_next(s)?;
+        // Newer jj versions include the revset symbol, e.g. `Working copy  (@) :`,
+        // which is prefixed with the workspace name in named workspaces
+        let workspace = opt(terminated(
+            delimited(
+                '(',
+                take_till(0.., |c: char| c == '@' || c == ')' || is_line_ending(c)),
+                "@)",
+            ),
+            space0,
+        ))
+        .parse_next(s)?;
+        let _ = ":".parse_next(s)?;
+        let _ = space1.parse_next(s)?;
+        let workspace = workspace.filter(|name| !name.is_empty());
+        let details = commit_details_ref.parse_next(s)?;
+        Ok(CommitDetailsRef {
+            workspace,
+            ..details
+        })
+    }
+}
+
+fn parent_commit_ref<'i>(
+    options: &ParseOptions,
+) -> impl FnMut(&mut &'i str) -> Result<CommitDetailsRef<'i>> + '_ {
+    move |s: &mut &'i str| {
+        let label = options.parent_label();
+        let _ = label
+            .context(StrContext::Expected(label_expected(
+                label,
+                "Parent commit:",
+            )))
+            .parse_next(s)?;
+        let _ = space0.parse_next(s)?;
+        // Newer jj versions include the revset symbol, e.g. `Parent commit (@-):`
+        let _ = opt(("(@-)", space0)).parse_next(s)?;
+        let _ = ":".parse_next(s)?;
+        let _ = space1.parse_next(s)?;
+        commit_details_ref.parse_next(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "change_type")]
+pub enum Commit {
+    WorkingCopy(CommitDetails),
+    ParentCommit(CommitDetails),
+}
+
+impl Commit {
+    pub fn change_id(&self) -> &str {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.change_id(),
+        }
+    }
+
+    pub fn commit_id(&self) -> Option<&str> {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.commit_id(),
+        }
+    }
+
+    pub fn change_id_short(&self, n: usize) -> &str {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.change_id_short(n),
+        }
+    }
+
+    pub fn commit_id_short(&self, n: usize) -> Option<&str> {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.commit_id_short(n),
+        }
+    }
+
+    pub fn is_root(&self) -> bool {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.is_root(),
+        }
+    }
+
+    pub fn is_divergent(&self) -> bool {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.is_divergent(),
+        }
+    }
+
+    pub fn empty(&self) -> bool {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.empty(),
+        }
+    }
+
+    pub fn is_conflicted(&self) -> bool {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.is_conflicted(),
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.is_hidden(),
+        }
+    }
+
+    /// Like `CommitDetails::matches_revset_symbol`, but also matching `@` (or `name@` in a named
+    /// workspace) for the working copy and `@-` for the parent
+    pub fn matches_revset_symbol(&self, symbol: &str) -> bool {
+        match self {
+            Self::WorkingCopy(details) => {
+                symbol == "@"
+                    || symbol
+                        .strip_suffix('@')
+                        .is_some_and(|name| Some(name) == details.workspace())
+                    || details.matches_revset_symbol(symbol)
+            }
+            Self::ParentCommit(details) => symbol == "@-" || details.matches_revset_symbol(symbol),
+        }
+    }
+
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.bookmarks(),
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.description(),
+        }
+    }
+
+    pub fn description_or<'a>(&'a self, default: &'a str) -> &'a str {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => {
+                details.description_or(default)
+            }
+        }
+    }
+
+    pub fn has_description(&self) -> bool {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.has_description(),
+        }
+    }
+
+    pub fn timestamp(&self) -> Option<&str> {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.timestamp(),
+        }
+    }
+
+    pub fn trailing(&self) -> Option<&str> {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.trailing(),
+        }
+    }
+
+    pub fn workspace(&self) -> Option<&str> {
+        match self {
+            Self::WorkingCopy(details) | Self::ParentCommit(details) => details.workspace(),
+        }
+    }
+}
+
+impl Display for Commit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WorkingCopy(details) => {
+                write!(f, "{details}")
+            }
+            Self::ParentCommit(details) => {
+                write!(f, "{details}")
+            }
+        }
+    }
+}
+
+fn is_section_start(options: &ParseOptions, line: &str) -> bool {
+    [
+        "Hint: ",
+        "Warning: ",
+        "The working copy ",
+        options.working_copy_label(),
+        options.parent_label(),
+    ]
+    .iter()
+    .any(|label| line.starts_with(label))
+}
+
+fn continuation_line<'i>(
+    options: &ParseOptions,
+) -> impl FnMut(&mut &'i str) -> Result<&'i str> + '_ {
+    move |s: &mut &'i str| {
+        take_till(1.., is_line_ending)
+            .verify(|line: &str| !is_section_start(options, line))
+            .parse_next(s)
+    }
+}
+
+// A hint or warning, along with any continuation lines that don't start a new section
+fn hint<'i>(options: &ParseOptions) -> impl FnMut(&mut &'i str) -> Result<&'i str> + '_ {
+    move |s: &mut &'i str| {
+        (
+            alt(("Hint: ", "Warning: ")),
+            take_till(0.., is_line_ending),
+            repeat(0.., (line_ending, continuation_line(options))).map(|()| ()),
+        )
+            .take()
+            .parse_next(s)
+    }
+}
+
+fn status_ref<'i>(
+    options: &ParseOptions,
+) -> impl FnMut(&mut &'i str) -> Result<StatusRef<'i>> + '_ {
+    move |s: &mut &'i str| {
+        // jj warns about a stale working copy before anything else
+        let mut hints: Vec<&str> =
+            repeat(0.., terminated(hint(options), line_ending)).parse_next(s)?;
+        let file_changes = file_changes_ref(options).parse_next(s)?;
+        // Tolerate blank lines between the file changes and the commits
+        repeat(0.., line_ending).map(|()| ()).parse_next(s)?;
+        let untracked = opt(terminated(untracked_paths, line_ending))
+            .map(Option::unwrap_or_default)
+            .parse_next(s)?;
+        repeat(0.., line_ending).map(|()| ()).parse_next(s)?;
+        let middle_hints: Vec<&str> =
+            repeat(0.., terminated(hint(options), line_ending)).parse_next(s)?;
+        hints.extend(middle_hints);
+        // jj can leave out the working copy, e.g. after `jj op restore`
+        let working_copy = if s.starts_with(options.parent_label()) {
+            None
+        } else {
+            let working_copy = working_copy_ref(options)
+                .context(StrContext::Label("working copy"))
+                .parse_next(s)?;
+            Some(working_copy)
+        };
+        // The parent line can be hidden by config, leaving nothing but hints after the working copy
+        let rest = s.trim_start();
+        let parent_hidden = working_copy.is_some()
+            && (rest.is_empty() || rest.starts_with("Hint: ") || rest.starts_with("Warning: "));
+        let mut parent_commits = Vec::new();
+        if !parent_hidden {
+            if working_copy.is_some() {
+                let _ = line_ending.parse_next(s)?;
+            }
+            let parent_commit = parent_commit_ref(options)
+                .context(StrContext::Label("parent commit"))
+                .parse_next(s)?;
+            parent_commits.push(parent_commit);
+            // A merge has a line per parent
+            let merged: Vec<_> = repeat(
+                0..,
+                preceded(
+                    (line_ending, peek(options.parent_label())),
+                    parent_commit_ref(options).context(StrContext::Label("parent commit")),
+                ),
+            )
+            .parse_next(s)?;
+            parent_commits.extend(merged);
+        }
+        let trailing_hints: Vec<&str> =
+            repeat(0.., preceded(line_ending, hint(options))).parse_next(s)?;
+        hints.extend(trailing_hints);
+        Ok(StatusRef {
+            file_changes,
+            untracked,
+            working_copy,
+            parent_commits,
+            hints,
+        })
+    }
+}
+
+fn status(options: &ParseOptions) -> impl FnMut(&mut &str) -> Result<Status> + '_ {
+    move |s: &mut &str| {
+        status_ref(options)
+            .map(|status| status.to_owned_with(options))
+            .parse_next(s)
+    }
+}
+
+/// The status parser with default options, for embedding in larger winnow parsers.
+///
+/// Advances `input` past the last line of the status (and any trailing hints), leaving the line
+/// ending after it unconsumed. Unlike `Status::from_str`, ANSI color codes aren't stripped.
+///
+/// ```
+/// use jj_status_parser::status_parser;
+/// use winnow::Parser;
+///
+/// let mut input = "The working copy has no changes.
+/// Working copy : qnxonnkx 60be3879 main | (no description set)
+/// Parent commit: xtryyrqp 75d612e0 main | main branch
+/// @  qnxonnkx";
+/// let status = status_parser.parse_next(&mut input).unwrap();
+/// assert!(status.is_clean());
+/// assert_eq!("\n@  qnxonnkx", input);
+/// ```
+pub fn status_parser(input: &mut &str) -> Result<Status> {
+    status(&ParseOptions::default()).parse_next(input)
+}
+
+/// Builds a `Status`, see `Status::builder`
+///
+/// ```
+/// use jj_status_parser::{Bookmark, CommitDetails, FileStatus, Status, WorkingCopyChange};
+/// use std::str::FromStr;
+///
+/// let status = Status::builder()
+///     .file_change(WorkingCopyChange::new(FileStatus::Added, None, "src/lib.rs"))
+///     .working_copy(CommitDetails::new(
+///         "qnxonnkx",
+///         "60be3879",
+///         false,
+///         vec![Bookmark::from_str("main").unwrap()],
+///         None,
+///     ))
+///     .parent_commit(CommitDetails::new("zzzzzzzz", "00000000", true, Vec::new(), None))
+///     .build();
+/// assert_eq!(
+///     "Working copy changes:
+/// A src/lib.rs
+/// Working copy : qnxonnkx 60be3879 main | (no description set)
+/// Parent commit: zzzzzzzz 00000000 (empty) (no description set)",
+///     status.to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StatusBuilder {
+    file_changes: Vec<WorkingCopyChange>,
+    untracked: Vec<PathBuf>,
+    working_copy: Option<CommitDetails>,
+    parent_commits: Vec<CommitDetails>,
+    hints: Vec<String>,
+}
+
+impl StatusBuilder {
+    pub fn file_change(mut self, change: WorkingCopyChange) -> Self {
+        self.file_changes.push(change);
+        self
+    }
+
+    pub fn untracked(mut self, path: impl Into<PathBuf>) -> Self {
+        self.untracked.push(path.into());
+        self
+    }
+
+    pub fn working_copy(mut self, details: CommitDetails) -> Self {
+        self.working_copy = Some(details);
+        self
+    }
+
+    /// Add a parent. Call again for each parent of a merge
+    pub fn parent_commit(mut self, details: CommitDetails) -> Self {
+        self.parent_commits.push(details);
+        self
+    }
+
+    /// A `Hint: ` or `Warning: ` line, including the prefix
+    pub fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hints.push(hint.into());
+        self
+    }
+
+    pub fn build(self) -> Status {
+        Status {
+            file_changes: self.file_changes,
+            untracked: self.untracked,
+            working_copy: self.working_copy.map(Commit::WorkingCopy),
+            parent_commits: self
+                .parent_commits
+                .into_iter()
+                .map(Commit::ParentCommit)
+                .collect(),
+            hints: self.hints,
+        }
+    }
+}
+
+/// A `Status` borrowing from the parsed input rather than allocating, see `parse_ref`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatusRef<'a> {
+    file_changes: Vec<WorkingCopyChangeRef<'a>>,
+    untracked: Vec<Cow<'a, str>>,
+    working_copy: Option<CommitDetailsRef<'a>>,
+    parent_commits: Vec<CommitDetailsRef<'a>>,
+    hints: Vec<&'a str>,
+}
+
+impl<'a> StatusRef<'a> {
+    pub fn file_changes(&self) -> &[WorkingCopyChangeRef<'a>] {
+        &self.file_changes
+    }
+
+    pub fn untracked(&self) -> &[Cow<'a, str>] {
+        &self.untracked
+    }
+
+    pub fn working_copy(&self) -> Option<&CommitDetailsRef<'a>> {
+        self.working_copy.as_ref()
+    }
+
+    pub fn parent_commit(&self) -> Option<&CommitDetailsRef<'a>> {
+        self.parent_commits.first()
+    }
+
+    pub fn parent_commits(&self) -> &[CommitDetailsRef<'a>] {
+        &self.parent_commits
+    }
+
+    pub fn hints(&self) -> &[&'a str] {
+        &self.hints
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.file_changes.is_empty()
+    }
+
+    pub fn to_owned(&self) -> Status {
+        self.to_owned_with(&ParseOptions::default())
+    }
+
+    fn to_owned_with(&self, options: &ParseOptions) -> Status {
+        let mut file_changes: Vec<_> = self
+            .file_changes
+            .iter()
+            .map(WorkingCopyChangeRef::to_owned)
+            .collect();
+        let mut untracked: Vec<_> = self
+            .untracked
+            .iter()
+            .map(|path| PathBuf::from(&**path))
+            .collect();
+        if options.strip_root_prefix {
+            for change in &mut file_changes {
+                change.strip_root_prefix();
+            }
+            for path in &mut untracked {
+                if let Ok(stripped) = path.strip_prefix("@") {
+                    *path = stripped.to_path_buf();
+                }
+            }
+        }
+        Status {
+            file_changes,
+            untracked,
+            working_copy: self
+                .working_copy
+                .as_ref()
+                .map(|details| Commit::WorkingCopy(details.to_owned_with(options))),
+            parent_commits: self
+                .parent_commits
+                .iter()
+                .map(|details| Commit::ParentCommit(details.to_owned_with(options)))
+                .collect(),
+            hints: self.hints.iter().map(|hint| hint.to_string()).collect(),
+        }
+    }
+}
+
+/// Parse `input` without copying it, for callers parsing often enough that allocating matters.
+///
+/// Unlike `Status::from_str`, ANSI color codes aren't stripped, since that would need a copy.
+///
+/// ```
+/// use jj_status_parser::parse_ref;
+///
+/// let input = "The working copy has no changes.
+/// Working copy : qnxonnkx 60be3879 main | (no description set)
+/// Parent commit: zzzzzzzz 00000000 (empty) (no description set)";
+/// let status = parse_ref(input).unwrap();
+/// assert_eq!("qnxonnkx", status.working_copy().unwrap().change_id());
+/// assert_eq!("main", status.working_copy().unwrap().bookmarks()[0].name());
+/// ```
+pub fn parse_ref(input: &str) -> std::result::Result<StatusRef<'_>, Error> {
+    if input.trim().is_empty() {
+        return Err(Error::EmptyInput);
+    }
+    // jj ends its output with a newline
+    terminated(status_ref(&ParseOptions::default()), multispace0)
+        .parse(input)
+        .map_err(Error::from_parse)
+}
+
+// Remove ANSI escape sequences (e.g. `\x1b[1;35m`) left in by `jj status --color always`.
+// Also returns each removed sequence as (offset in the stripped text, length in bytes)
+fn strip_ansi(s: &str) -> (Cow<'_, str>, Vec<(usize, usize)>) {
+    if !s.contains('\x1b') {
+        return (Cow::Borrowed(s), Vec::new());
+    }
+    let mut stripped = String::with_capacity(s.len());
+    let mut removed = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+        if chars.next().is_some_and(|(_, c)| c == '[') {
+            // Skip parameters up to and including the final byte
+            for (_, c) in chars.by_ref() {
+                if ('\x40'..='\x7e').contains(&c) {
+                    break;
+                }
+            }
+        }
+        let end = chars.peek().map_or(s.len(), |&(end, _)| end);
+        removed.push((stripped.len(), end - start));
+    }
+    (Cow::Owned(stripped), removed)
+}
+
+// Map an offset into the text `strip_ansi` returned back onto the original text
+fn unstripped_offset(offset: usize, removed: &[(usize, usize)]) -> usize {
+    let skipped: usize = removed
+        .iter()
+        .take_while(|(at, _)| *at <= offset)
+        .map(|(_, len)| len)
+        .sum();
+    offset + skipped
+}
+
+impl FromStr for Status {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Status::parse_with_options(s, &ParseOptions::default())
+    }
+}
+
+impl TryFrom<&[u8]> for Status {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> std::result::Result<Self, Self::Error> {
+        let s = std::str::from_utf8(bytes).map_err(|source| Error::InvalidUtf8 { source })?;
+        Status::from_str(s)
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.file_changes.is_empty() {
+            writeln!(f, "The working copy has no changes.")?;
+        } else {
+            writeln!(f, "Working copy changes:")?;
+            for change in &self.file_changes {
+                writeln!(f, "{change}")?;
+            }
+        }
+        if !self.untracked.is_empty() {
+            writeln!(f, "Untracked paths:")?;
+            for path in &self.untracked {
+                writeln!(f, "? {}", path.display())?;
+            }
+        }
+        if let Some(working_copy) = &self.working_copy {
+            match working_copy.workspace() {
+                Some(workspace) => write!(f, "Working copy  ({workspace}@) : {working_copy}")?,
+                None => write!(f, "Working copy : {working_copy}")?,
+            }
+        }
+        for (i, parent_commit) in self.parent_commits.iter().enumerate() {
+            if self.working_copy.is_some() || i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "Parent commit: {parent_commit}")?;
+        }
+        for hint in &self.hints {
+            write!(f, "\n{hint}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use s_string::s;
+    use std::collections::BTreeSet;
+    use winnow::ascii::newline;
+    use winnow::error::ContextError;
+
+    const HEADER: &str = "Working copy changes:";
+    const FILE1: &str = "A src/lib.rs";
+    const FILE2: &str = "A src/main.rs";
+    const WORKING: &str = "Working copy : qnxonnkx 60be3879 main | (no description set)";
+    const PARENT: &str = "Parent commit: zzzzzzzz 00000000 (empty) (no description set)";
+
+    fn local_bookmark(name: &str) -> Bookmark {
+        Bookmark {
+            name: name.to_string(),
+            remote: None,
+            conflicted: false,
+            out_of_sync: false,
+            ahead: None,
+            behind: None,
+        }
+    }
+
+    fn remote_bookmark(name: &str, remote: &str) -> Bookmark {
+        Bookmark {
+            remote: Some(remote.to_string()),
+            ..local_bookmark(name)
+        }
+    }
+
+    #[test]
+    fn test_parse_change_id() {
+        let mut input = "qnxonnkx";
+        let expected = "qnxonnkx";
+        let actual = change_id(&mut input);
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_commit_id() {
+        let mut input = "60be3879";
+        let expected = "60be3879";
+        let actual = commit_id(&mut input);
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_file_change() {
+        let mut input = FILE1;
+        let expected = WorkingCopyChange {
+            status: FileStatus::Added,
+            from_path: None,
+            path: PathBuf::from("src/lib.rs"),
+        };
+        let actual = file_change_ref(&mut input).map(|change| change.to_owned());
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_file_changes() {
+        let input = [HEADER, FILE1, FILE2].join("\n");
+        let mut input = input.as_str();
+
+        let expected = vec![
+            WorkingCopyChange {
+                status: FileStatus::Added,
+                from_path: None,
+                path: PathBuf::from("src/lib.rs"),
+            },
+            WorkingCopyChange {
+                status: FileStatus::Added,
+                from_path: None,
+                path: PathBuf::from("src/main.rs"),
+            },
+        ];
+        let actual = file_changes_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|changes| changes.iter().map(WorkingCopyChangeRef::to_owned).collect());
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_file_changes_header_trailing_spaces() {
+        let input = ["Working copy changes:  \t", FILE1, FILE2].join("\n");
+        let mut input = input.as_str();
+
+        let actual = file_changes_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .unwrap();
+        assert_eq!(2, actual.len());
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_details_1() {
+        let mut input = "qnxonnkx 60be3879 main | (no description set)";
+        let expected = CommitDetails {
+            change_id: String::from("qnxonnkx"),
+            divergent: false,
+            commit_id: Some(String::from("60be3879")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: vec![local_bookmark("main")],
+            description: None,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        };
+        let actual = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned());
+        assert_eq!(Ok(expected), actual)
+    }
+
+    #[test]
+    fn test_parse_details_2() {
+        let mut input = "zzzzzzzz 00000000 (empty) (no description set)";
+        let expected = CommitDetails {
+            change_id: s!("zzzzzzzz"),
+            divergent: false,
+            commit_id: Some(s!("00000000")),
+            empty: true,
+            conflict: false,
+            hidden: false,
+            bookmarks: Vec::new(),
+            description: None,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        };
+        let actual = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned());
+        assert_eq!(Ok(expected), actual)
+    }
+
+    #[test]
+    fn test_parse_working_copy() {
+        let mut input = WORKING;
+        let expected = Commit::WorkingCopy(CommitDetails {
+            change_id: s!("qnxonnkx"),
+            divergent: false,
+            commit_id: Some(s!("60be3879")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: vec![local_bookmark("main")],
+            description: None,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        });
+        let actual = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()));
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_empty_description() {
+        let mut input = EMPTY_DESCRIPTION;
+        let expected = None;
+        let actual = description(&mut input);
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_parent_commit() {
+        let mut input = PARENT;
+        let expected = Commit::ParentCommit(CommitDetails {
+            change_id: s!("zzzzzzzz"),
+            divergent: false,
+            commit_id: Some(s!("00000000")),
+            empty: true,
+            conflict: false,
+            hidden: false,
+            bookmarks: Vec::new(),
+            description: None,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        });
+        let actual = parent_commit_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::ParentCommit(details.to_owned()));
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_status_from_str() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+
+        let expected = Status {
+            file_changes: vec![
+                WorkingCopyChange {
+                    status: FileStatus::Added,
+                    from_path: None,
+                    path: PathBuf::from("src/lib.rs"),
+                },
+                WorkingCopyChange {
+                    status: FileStatus::Added,
+                    from_path: None,
+                    path: PathBuf::from("src/main.rs"),
+                },
+            ],
+            untracked: Vec::new(),
+            working_copy: Some(Commit::WorkingCopy(CommitDetails {
+                change_id: s!("qnxonnkx"),
+                divergent: false,
+                commit_id: Some(s!("60be3879")),
+                empty: false,
+                conflict: false,
+                hidden: false,
+                bookmarks: vec![local_bookmark("main")],
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })),
+            parent_commits: vec![Commit::ParentCommit(CommitDetails {
+                change_id: s!("zzzzzzzz"),
+                divergent: false,
+                commit_id: Some(s!("00000000")),
+                empty: true,
+                conflict: false,
+                hidden: false,
+                bookmarks: Vec::new(),
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })],
+            hints: Vec::new(),
+        };
+        let actual = Status::from_str(&input);
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn test_no_changes_status_from_str() {
+        let input = ["The working copy has no changes.", WORKING, PARENT].join("\n");
+
+        let expected = Status {
+            file_changes: Vec::new(),
+            untracked: Vec::new(),
+            working_copy: Some(Commit::WorkingCopy(CommitDetails {
+                change_id: s!("qnxonnkx"),
+                divergent: false,
+                commit_id: Some(s!("60be3879")),
+                empty: false,
+                conflict: false,
+                hidden: false,
+                bookmarks: vec![local_bookmark("main")],
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })),
+            parent_commits: vec![Commit::ParentCommit(CommitDetails {
+                change_id: s!("zzzzzzzz"),
+                divergent: false,
+                commit_id: Some(s!("00000000")),
+                empty: true,
+                conflict: false,
+                hidden: false,
+                bookmarks: Vec::new(),
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })],
+            hints: Vec::new(),
+        };
+        let actual = Status::from_str(&input);
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn test_parse_working_copy_2() {
+        let mut input = "Working copy : oonwmqxn a3d80cec (no description set)";
+        let expected = Commit::WorkingCopy(CommitDetails {
+            change_id: s!("oonwmqxn"),
+            divergent: false,
+            commit_id: Some(s!("a3d80cec")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: Vec::new(),
+            description: None,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        });
+        let actual = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()));
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_parent_commit_2() {
+        let mut input = "Parent commit: xtryyrqp 75d612e0 main@origin | main branch";
+        let expected = Commit::ParentCommit(CommitDetails {
+            change_id: s!("xtryyrqp"),
+            divergent: false,
+            commit_id: Some(s!("75d612e0")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: vec![remote_bookmark("main", "origin")],
+            description: Some(s!("main branch")),
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        });
+        let actual = parent_commit_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::ParentCommit(details.to_owned()));
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_status_from_str_2() {
+        let mut input = r#"Working copy changes:
+M src/lib.rs
+Working copy : oonwmqxn a3d80cec (no description set)
+Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#;
+
+        let _ = file_changes_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .unwrap();
+        assert_eq!(
+            r#"
+Working copy : oonwmqxn a3d80cec (no description set)
+Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#,
+            input
+        );
+
+        let _ = newline::<&str, ContextError>
+            .parse_next(&mut input)
+            .unwrap();
+        assert_eq!(
+            r#"Working copy : oonwmqxn a3d80cec (no description set)
+Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#,
+            input
+        );
+
+        let foo = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()))
+            .unwrap();
+        assert_eq!(
+            Commit::WorkingCopy(CommitDetails {
+                change_id: s!("oonwmqxn"),
+                divergent: false,
+                commit_id: Some(s!("a3d80cec")),
+                empty: false,
+                conflict: false,
+                hidden: false,
+                bookmarks: Vec::new(),
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            }),
+            foo
+        );
+        assert_eq!(
+            r#"
+Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#,
+            input
+        );
+
+        let _ = newline::<&str, ContextError>
+            .parse_next(&mut input)
+            .unwrap();
+        assert_eq!(
+            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
+            input
+        );
+
+        let _ = parent_commit_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::ParentCommit(details.to_owned()))
+            .unwrap();
+        assert_eq!("", input);
+
+        let input = r#"Working copy changes:
+M src/lib.rs
+Working copy : oonwmqxn a3d80cec (no description set)
+Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#;
+
+        let expected = Status {
+            file_changes: vec![WorkingCopyChange {
+                status: FileStatus::Modified,
+                from_path: None,
+                path: PathBuf::from("src/lib.rs"),
+            }],
+            untracked: Vec::new(),
+            working_copy: Some(Commit::WorkingCopy(CommitDetails {
+                change_id: s!("oonwmqxn"),
+                divergent: false,
+                commit_id: Some(s!("a3d80cec")),
+                empty: false,
+                conflict: false,
+                hidden: false,
+                bookmarks: Vec::new(),
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })),
+            parent_commits: vec![Commit::ParentCommit(CommitDetails {
+                change_id: s!("xtryyrqp"),
+                divergent: false,
+                commit_id: Some(s!("75d612e0")),
+                empty: false,
+                conflict: false,
+                hidden: false,
+                bookmarks: vec![remote_bookmark("main", "origin")],
+                description: Some(s!("main branch")),
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })],
+            hints: Vec::new(),
+        };
+        let actual = Status::from_str(input);
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn test_status_no_changes() {
+        let input = [WORKING, PARENT].join("\n");
+        let expected = Status {
+            file_changes: Vec::new(),
+            untracked: Vec::new(),
+            working_copy: Some(Commit::WorkingCopy(CommitDetails {
+                change_id: s!("qnxonnkx"),
+                divergent: false,
+                commit_id: Some(s!("60be3879")),
+                empty: false,
+                conflict: false,
+                hidden: false,
+                bookmarks: vec![local_bookmark("main")],
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })),
+            parent_commits: vec![Commit::ParentCommit(CommitDetails {
+                change_id: s!("zzzzzzzz"),
+                divergent: false,
+                commit_id: Some(s!("00000000")),
+                empty: true,
+                conflict: false,
+                hidden: false,
+                bookmarks: Vec::new(),
+                description: None,
+                timestamp: None,
+                trailing: None,
+                workspace: None,
+            })],
+            hints: Vec::new(),
+        };
+        let actual = Status::from_str(&input);
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn test_status_serde_round_trip() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let expected = Status::from_str(&input).unwrap();
+        let json = serde_json::to_string(&expected).unwrap();
+        let actual: Status = serde_json::from_str(&json).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_status_deserialize_single_parent_commit() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let expected = Status::from_str(&input).unwrap();
+        let mut json = serde_json::to_value(&expected).unwrap();
+        let object = json.as_object_mut().unwrap();
+        let parents = object.remove("parent_commits").unwrap();
+        object.insert(s!("parent_commit"), parents[0].clone());
+        let actual: Status = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(expected, actual);
+
+        let object = json.as_object_mut().unwrap();
+        object.insert(s!("parent_commit"), serde_json::Value::Null);
+        let actual: Status = serde_json::from_value(json.clone()).unwrap();
+        assert!(actual.parent_commits().is_empty());
+
+        json.as_object_mut().unwrap().remove("parent_commit");
+        let actual: Status = serde_json::from_value(json).unwrap();
+        assert!(actual.parent_commits().is_empty());
+    }
+
+    #[test]
+    fn test_parse_nested_rename() {
+        let mut input = "R src/old/a.rs => src/new/b.rs";
+        let expected = WorkingCopyChange {
+            status: FileStatus::Renamed,
+            from_path: Some(PathBuf::from("src/old/a.rs")),
+            path: PathBuf::from("src/new/b.rs"),
+        };
+        let actual = file_change_ref(&mut input)
+            .map(|change| change.to_owned())
+            .unwrap();
+        assert_eq!(expected, actual);
+        assert_eq!("", input);
+
+        let from_path = actual.from_path.as_ref().unwrap();
+        let from_components: Vec<_> = from_path.iter().collect();
+        assert_eq!(vec!["src", "old", "a.rs"], from_components);
+        let components: Vec<_> = actual.path.iter().collect();
+        assert_eq!(vec!["src", "new", "b.rs"], components);
+    }
+
+    #[test]
+    fn test_file_status_git_xy_round_trip() {
+        let statuses = [
+            FileStatus::Added,
+            FileStatus::Modified,
+            FileStatus::Deleted,
+            FileStatus::Renamed,
+        ];
+        for status in statuses {
+            let (x, y) = status.to_git_xy();
+            assert_eq!(Some(status), FileStatus::from_git_xy(x, y));
+        }
+        assert_eq!(('A', ' '), FileStatus::Added.to_git_xy());
+        assert_eq!(('R', ' '), FileStatus::Renamed.to_git_xy());
+    }
+
+    #[test]
+    fn test_file_status_from_git_xy_approximations() {
+        assert_eq!(
+            Some(FileStatus::Modified),
+            FileStatus::from_git_xy(' ', 'M')
+        );
+        assert_eq!(
+            Some(FileStatus::Modified),
+            FileStatus::from_git_xy('T', ' ')
+        );
+        assert_eq!(Some(FileStatus::Added), FileStatus::from_git_xy('A', 'M'));
+        assert_eq!(None, FileStatus::from_git_xy('?', '?'));
+        assert_eq!(None, FileStatus::from_git_xy('U', 'U'));
+    }
+
+    #[test]
+    fn test_file_status_serde_round_trip() {
+        let statuses = [
+            (FileStatus::Added, "\"A\""),
+            (FileStatus::Modified, "\"M\""),
+            (FileStatus::Deleted, "\"D\""),
+            (FileStatus::Renamed, "\"R\""),
+        ];
+        for (status, json) in statuses {
+            assert_eq!(json, serde_json::to_string(&status).unwrap());
+            let actual: FileStatus = serde_json::from_str(json).unwrap();
+            assert_eq!(status, actual);
+        }
+        assert!(serde_json::from_str::<FileStatus>("\"Added\"").is_err());
+    }
+
+    #[test]
+    fn test_status_from_str_crlf() {
+        let lf = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let crlf = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\r\n");
+        let expected = Status::from_str(&lf).unwrap();
+        let actual = Status::from_str(&crlf);
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn test_parse_parent_commit_ids_only() {
+        let mut input = "Parent commit: zzzzzzzz 00000000";
+        let expected = Commit::ParentCommit(CommitDetails {
+            change_id: s!("zzzzzzzz"),
+            divergent: false,
+            commit_id: Some(s!("00000000")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: Vec::new(),
+            description: None,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        });
+        let actual = parent_commit_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::ParentCommit(details.to_owned()));
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_status_to_json_writer() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let mut buffer: Vec<u8> = Vec::new();
+        status.to_json_writer(&mut buffer).unwrap();
+        let expected = serde_json::to_string(&status).unwrap();
+        assert_eq!(expected, String::from_utf8(buffer).unwrap());
+    }
+
+    #[test]
+    fn test_parse_quoted_path() {
+        let mut input = r#"A "src/my file.rs""#;
+        let expected = WorkingCopyChange {
+            status: FileStatus::Added,
+            from_path: None,
+            path: PathBuf::from("src/my file.rs"),
+        };
+        let actual = file_change_ref(&mut input).map(|change| change.to_owned());
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+
+        let mut input = r#"M "say \"hi\".txt""#;
+        let actual = file_change_ref(&mut input)
+            .map(|change| change.to_owned())
+            .unwrap();
+        assert_eq!(PathBuf::from(r#"say "hi".txt"#), actual.path);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_quoted_rename() {
+        let mut input = r#"R "a b.rs" => c.rs"#;
+        let actual = file_change_ref(&mut input)
+            .map(|change| change.to_owned())
+            .unwrap();
+        assert_eq!(Some(PathBuf::from("a b.rs")), actual.from_path);
+        assert_eq!(PathBuf::from("c.rs"), actual.path);
+
+        let mut input = r#"R "a b.rs"junk => c.rs"#;
+        assert!(
+            file_change_ref(&mut input)
+                .map(|change| change.to_owned())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_details_timestamp() {
+        let mut input = "qnxonnkx 60be3879 main | fix the parser 2024-01-02T10:00:00Z";
+        let expected = CommitDetails {
+            change_id: s!("qnxonnkx"),
+            divergent: false,
+            commit_id: Some(s!("60be3879")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: vec![local_bookmark("main")],
+            description: Some(s!("fix the parser")),
+            timestamp: Some(s!("2024-01-02T10:00:00Z")),
+            trailing: None,
+            workspace: None,
+        };
+        let actual = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned());
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+
+        let mut input = "qnxonnkx 60be3879 main | fix the parser";
+        let expected = CommitDetails {
+            change_id: s!("qnxonnkx"),
+            divergent: false,
+            commit_id: Some(s!("60be3879")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: vec![local_bookmark("main")],
+            description: Some(s!("fix the parser")),
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        };
+        let actual = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned());
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_details_timestamp_without_description() {
+        let mut input =
+            "zzzzzzzz 00000000 (empty) (no description set) 2024-01-02T10:00:00.5+01:00";
+        let actual = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!(None, actual.description);
+        assert_eq!(Some("2024-01-02T10:00:00.5+01:00"), actual.timestamp());
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_changes_under() {
+        let input = [
+            HEADER,
+            "M crates/core/src/lib.rs",
+            "A crates/core/src/parse/mod.rs",
+            "M crates/cli/src/main.rs",
+            "M crates/core-extra/lib.rs",
+            "A README.md",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+
+        let actual: Vec<&Path> = status
+            .changes_under(Path::new("crates/core"))
+            .iter()
+            .map(|change| change.path())
+            .collect();
+        let expected = vec![
+            Path::new("crates/core/src/lib.rs"),
+            Path::new("crates/core/src/parse/mod.rs"),
+        ];
+        assert_eq!(expected, actual);
+
+        assert_eq!(5, status.changes_under(Path::new("")).len());
+        assert!(status.changes_under(Path::new("docs")).is_empty());
+    }
+
+    #[test]
+    fn test_status_parse_partial() {
+        let input = [
+            HEADER,
+            FILE1,
+            FILE2,
+            WORKING,
+            PARENT,
+            "output from another command",
+        ]
+        .join("\n");
+        let expected =
+            Status::from_str(&[HEADER, FILE1, FILE2, WORKING, PARENT].join("\n")).unwrap();
+        let (actual, remaining) = Status::parse_partial(&input).unwrap();
+        assert_eq!(expected, actual);
+        assert_eq!("\noutput from another command", remaining);
+
+        assert!(Status::from_str(&input).is_err());
+    }
+
+    #[test]
+    fn test_status_parse_partial_described_parent() {
+        let parent = "Parent commit: xtryyrqp 75d612e0 main | main branch";
+        let input = [
+            HEADER,
+            FILE1,
+            WORKING,
+            parent,
+            "output from another command",
+        ]
+        .join("\n");
+        let (status, remaining) = Status::parse_partial(&input).unwrap();
+        assert_eq!("main branch", status.parent_commit().unwrap().description());
+        assert_eq!("\noutput from another command", remaining);
+    }
+
+    #[test]
+    fn test_parse_bookmark_sync_compact() {
+        let mut input = "main (+2/-1) |";
+        let expected = Bookmark {
+            ahead: Some(2),
+            behind: Some(1),
+            ..local_bookmark("main")
+        };
+        assert_eq!(
+            Ok(vec![expected]),
+            bookmark_column(&mut input)
+                .map(|bookmarks| bookmarks.into_iter().map(BookmarkRef::to_owned).collect())
+        );
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_parse_bookmark_sync_verbose() {
+        let mut input = "main@origin (behind by 3 commits) |";
+        let expected = Bookmark {
+            behind: Some(3),
+            ..remote_bookmark("main", "origin")
+        };
+        assert_eq!(
+            Ok(vec![expected]),
+            bookmark_column(&mut input)
+                .map(|bookmarks| bookmarks.into_iter().map(BookmarkRef::to_owned).collect())
+        );
+        assert_eq!("", input);
+
+        let mut input = "qnxonnkx 60be3879 main (ahead by 1 commit) | wip";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        let bookmark = &details.bookmarks()[0];
+        assert_eq!("main", bookmark.name());
+        assert_eq!(Some(1), bookmark.ahead());
+        assert_eq!(None, bookmark.behind());
+        assert_eq!("wip", details.description());
+    }
+
+    #[test]
+    fn test_status_trailing_hints() {
+        let input = [
+            HEADER,
+            FILE1,
+            FILE2,
+            WORKING,
+            PARENT,
+            "Warning: There are unresolved conflicts at these paths:",
+            "src/lib.rs    2-sided conflict",
+            "Hint: Use `jj new` to start a new change.",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(2, status.file_changes().len());
+        assert_eq!("zzzzzzzz", status.parent_commit().unwrap().change_id());
+        assert_eq!(
+            vec![
+                "Warning: There are unresolved conflicts at these paths:\nsrc/lib.rs    2-sided conflict",
+                "Hint: Use `jj new` to start a new change.",
+            ],
+            status.hints()
+        );
+    }
+
+    #[test]
+    fn test_status_hint_before_working_copy() {
+        let input = [
+            "The working copy has no changes.",
+            "Warning: The working copy is stale",
+            "(run `jj workspace update-stale`)",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!("qnxonnkx", status.working_copy().unwrap().change_id());
+        assert_eq!(
+            vec!["Warning: The working copy is stale\n(run `jj workspace update-stale`)"],
+            status.hints()
+        );
+    }
+
+    #[test]
+    fn test_status_hint_before_header() {
+        let input = [
+            "Hint: The working copy is stale (not updated since operation 3f2c9a1b).",
+            "Hint: Run `jj workspace update-stale` to update it.",
+            HEADER,
+            FILE1,
+            WORKING,
+            PARENT,
+            "Hint: Use `jj new` to start a new change.",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(
+            vec![
+                "Hint: The working copy is stale (not updated since operation 3f2c9a1b).",
+                "Hint: Run `jj workspace update-stale` to update it.",
+                "Hint: Use `jj new` to start a new change.",
+            ],
+            status.hints()
+        );
+        assert_eq!(1, status.file_changes().len());
+
+        let input = [
+            "Warning: The working copy is stale",
+            "The working copy has no changes.",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert!(status.is_clean());
+        assert_eq!(vec!["Warning: The working copy is stale"], status.hints());
+    }
+
+    #[test]
+    fn test_description_or() {
+        let input = [WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!("(none)", status.working_copy_description_or("(none)"));
+        assert_eq!("(none)", status.parent_description_or("(none)"));
+
+        let input = [
+            "Working copy : oonwmqxn a3d80cec add parser",
+            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!("add parser", status.working_copy_description_or("(none)"));
+        assert_eq!("main branch", status.parent_description_or("(none)"));
+    }
+
+    #[test]
+    fn test_parse_revset_marker_labels() {
+        let mut old = WORKING;
+        let mut new = "Working copy  (@) : qnxonnkx 60be3879 main | (no description set)";
+        assert_eq!(
+            working_copy_ref(&ParseOptions::default())
+                .parse_next(&mut old)
+                .map(|details| Commit::WorkingCopy(details.to_owned())),
+            working_copy_ref(&ParseOptions::default())
+                .parse_next(&mut new)
+                .map(|details| Commit::WorkingCopy(details.to_owned()))
+        );
+        assert_eq!("", new);
+
+        let mut old = PARENT;
+        let mut new = "Parent commit (@-): zzzzzzzz 00000000 (empty) (no description set)";
+        assert_eq!(
+            parent_commit_ref(&ParseOptions::default())
+                .parse_next(&mut old)
+                .map(|details| Commit::ParentCommit(details.to_owned())),
+            parent_commit_ref(&ParseOptions::default())
+                .parse_next(&mut new)
+                .map(|details| Commit::ParentCommit(details.to_owned()))
+        );
+        assert_eq!("", new);
+
+        let input = [
+            "The working copy has no changes.",
+            "Working copy  (@) : qnxonnkx 60be3879 main | (no description set)",
+            "Parent commit (@-): zzzzzzzz 00000000 (empty) (no description set)",
+        ]
+        .join("\n");
+        let expected =
+            Status::from_str(&["The working copy has no changes.", WORKING, PARENT].join("\n"));
+        assert_eq!(expected, Status::from_str(&input));
+    }
+
+    #[test]
+    fn test_parse_multi_line_description() {
+        let input = [
+            "Working copy : oonwmqxn a3d80cec Add a parser",
+            "  that handles multi-line descriptions",
+            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(
+            "Add a parser\n  that handles multi-line descriptions",
+            status.working_copy().unwrap().description()
+        );
+        assert_eq!("main branch", status.parent_commit().unwrap().description());
+    }
+
+    #[test]
+    fn test_parse_named_workspace() {
+        let mut input = "Working copy  (docs@) : qnxonnkx 60be3879 main | (no description set)";
+        let actual = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()))
+            .unwrap();
+        assert_eq!(Some("docs"), actual.workspace());
+        assert_eq!("qnxonnkx", actual.change_id());
+        assert_eq!("", input);
+
+        let mut input = "Working copy  (@) : qnxonnkx 60be3879 main | (no description set)";
+        let actual = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()))
+            .unwrap();
+        assert_eq!(None, actual.workspace());
+    }
+
+    #[test]
+    fn test_working_copy_change_ord() {
+        let change = |status, path: &str| WorkingCopyChange {
+            status,
+            from_path: None,
+            path: PathBuf::from(path),
+        };
+        let mut changes = BTreeSet::new();
+        changes.insert(change(FileStatus::Modified, "src/main.rs"));
+        changes.insert(change(FileStatus::Added, "README.md"));
+        changes.insert(change(FileStatus::Deleted, "src/lib.rs"));
+        changes.insert(change(FileStatus::Added, "src/lib.rs"));
+        changes.insert(change(FileStatus::Modified, "src/main.rs"));
+
+        let actual: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        let expected = vec![
+            "A README.md",
+            "A src/lib.rs",
+            "D src/lib.rs",
+            "M src/main.rs",
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_parse_divergent_working_copy() {
+        let mut input = "Working copy : qpvuntsm?? 8f3a1c2d (no description set)";
+        let expected = Commit::WorkingCopy(CommitDetails {
+            change_id: s!("qpvuntsm"),
+            divergent: true,
+            commit_id: Some(s!("8f3a1c2d")),
+            empty: false,
+            conflict: false,
+            hidden: false,
+            bookmarks: Vec::new(),
+            description: None,
+            timestamp: None,
+            trailing: None,
+            workspace: None,
+        });
+        let actual = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()));
+        assert_eq!(Ok(expected), actual);
+        assert_eq!("", input);
+        assert!(
+            !commit_details_ref
+                .parse_next(&mut "qpvuntsm 8f3a1c2d")
+                .map(|details| details.to_owned())
+                .unwrap()
+                .is_divergent()
+        );
+    }
+
+    #[test]
+    fn test_parse_many_file_changes() {
+        let files: Vec<String> = (0..5000)
+            .map(|i| format!("M src/module_{}/file_{i}.rs", i % 50))
+            .collect();
+        let mut lines: Vec<&str> = vec![HEADER];
+        lines.extend(files.iter().map(String::as_str));
+        lines.extend([WORKING, PARENT]);
+        let input = lines.join("\n");
+
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(5000, status.file_changes().len());
+        assert_eq!(
+            Path::new("src/module_49/file_4999.rs"),
+            status.file_changes()[4999].path()
+        );
+    }
+
+    #[test]
+    fn test_is_root() {
+        let mut input = PARENT;
+        assert!(
+            parent_commit_ref(&ParseOptions::default())
+                .parse_next(&mut input)
+                .map(|details| Commit::ParentCommit(details.to_owned()))
+                .unwrap()
+                .is_root()
+        );
+
+        let mut input = "Parent commit: zz 0 (empty) (no description set)";
+        let actual = parent_commit_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::ParentCommit(details.to_owned()))
+            .unwrap();
+        assert!(actual.is_root());
+        assert_eq!(Some("0"), actual.commit_id());
+        assert_eq!("", input);
+
+        let mut input = WORKING;
+        assert!(
+            !working_copy_ref(&ParseOptions::default())
+                .parse_next(&mut input)
+                .map(|details| Commit::WorkingCopy(details.to_owned()))
+                .unwrap()
+                .is_root()
+        );
+    }
+
+    #[test]
+    fn test_json_with_summary() {
+        let input = [HEADER, FILE1, "M src/main.rs", WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&status.json_with_summary().unwrap()).unwrap();
+
+        assert_eq!("qnxonnkx", json["working_copy"]["change_id"]);
+        assert_eq!("zzzzzzzz", json["parent_commits"][0]["change_id"]);
+        assert_eq!("src/lib.rs", json["file_changes"][0]["path"]);
+        assert_eq!(2, json["summary"]["files"]);
+        assert_eq!(1, json["summary"]["added"]);
+        assert_eq!(1, json["summary"]["modified"]);
+        assert_eq!(0, json["summary"]["deleted"]);
+        assert_eq!("dirty", json["summary"]["state"]);
+
+        let status = Status::from_str(&[WORKING, PARENT].join("\n")).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&status.json_with_summary().unwrap()).unwrap();
+        assert_eq!(0, json["summary"]["files"]);
+        assert_eq!("fresh", json["summary"]["state"]);
+
+        let cases = [
+            (
+                vec![
+                    WORKING,
+                    "Parent commit: xtryyrqp 75d612e0 main | main branch",
+                ],
+                "clean",
+            ),
+            (
+                vec![
+                    "Working copy : qnxonnkx 60be3879 (conflict) main | (no description set)",
+                    PARENT,
+                ],
+                "conflicted",
+            ),
+            (
+                vec![
+                    HEADER,
+                    "M src/lib.rs",
+                    WORKING,
+                    PARENT,
+                    "Warning: There are unresolved conflicts at these paths:",
+                    "src/lib.rs    2-sided conflict",
+                ],
+                "conflicted",
+            ),
+        ];
+        for (lines, state) in cases {
+            let status = Status::from_str(&lines.join("\n")).unwrap();
+            let json: serde_json::Value =
+                serde_json::from_str(&status.json_with_summary().unwrap()).unwrap();
+            assert_eq!(state, json["summary"]["state"]);
+        }
+    }
+
+    #[test]
+    fn test_is_clean() {
+        let clean = ["The working copy has no changes.", WORKING, PARENT].join("\n");
+        assert!(Status::from_str(&clean).unwrap().is_clean());
+
+        let dirty = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        assert!(!Status::from_str(&dirty).unwrap().is_clean());
+
+        let conflicted = [
+            "The working copy has no changes.",
+            "Working copy : qnxonnkx 60be3879 (conflict) main | (no description set)",
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&conflicted).unwrap();
+        assert!(!status.is_clean());
+        assert_eq!(RepoState::Conflicted, status.state());
+    }
+
+    #[test]
+    fn test_no_changes_spacing_variants() {
+        let expected = Status::from_str(&[WORKING, PARENT].join("\n")).unwrap();
+
+        let input = ["The working copy has no changes.", WORKING, PARENT].join("\n");
+        assert_eq!(Ok(&expected), Status::from_str(&input).as_ref());
+
+        let input = ["The working copy has no changes.", "", WORKING, PARENT].join("\n");
+        assert_eq!(Ok(&expected), Status::from_str(&input).as_ref());
+    }
+
+    #[test]
+    fn test_bookmark_string_round_trip() {
+        let bookmarks = [
+            local_bookmark("main"),
+            remote_bookmark("main", "origin"),
+            Bookmark {
+                conflicted: true,
+                ..local_bookmark("main")
+            },
+            Bookmark {
+                conflicted: true,
+                ..remote_bookmark("main", "origin")
+            },
+            Bookmark {
+                out_of_sync: true,
+                ahead: Some(2),
+                behind: Some(1),
+                ..local_bookmark("feature")
+            },
+            Bookmark {
+                behind: Some(1),
+                ..remote_bookmark("main", "upstream")
+            },
+        ];
+        for bookmark in bookmarks {
+            let rendered = bookmark.to_string();
+            assert_eq!(Ok(bookmark), Bookmark::from_str(&rendered), "{rendered}");
+        }
+
+        assert_eq!("main@origin", remote_bookmark("main", "origin").to_string());
+        let conflicted = Bookmark::from_str("main??").unwrap();
+        assert_eq!("main", conflicted.name());
+        assert!(conflicted.is_conflicted());
+        assert_eq!("main??", conflicted.to_string());
+        assert!(Bookmark::from_str("main extra").is_err());
+    }
+
+    #[test]
+    fn test_parse_details_tab_separated() {
+        let mut spaces = "qnxonnkx 60be3879 main | (no description set)";
+        let mut tabs = "qnxonnkx\t60be3879\tmain | (no description set)";
+        let expected = commit_details_ref
+            .parse_next(&mut spaces)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!(
+            Ok(expected),
+            commit_details_ref
+                .parse_next(&mut tabs)
+                .map(|details| details.to_owned())
+        );
+        assert_eq!("", tabs);
+
+        let mut input = "Working copy :\tqnxonnkx\t60be3879 (empty)\t(no description set)";
+        let actual = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()))
+            .unwrap();
+        assert_eq!(Some("60be3879"), actual.commit_id());
+        assert!(actual.empty());
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_local_and_remote_bookmarks() {
+        let input = [
+            "Working copy : oonwmqxn a3d80cec main | (no description set)",
+            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(vec![&local_bookmark("main")], status.local_bookmarks());
+        assert_eq!(
+            vec![&remote_bookmark("main", "origin")],
+            status.remote_bookmarks()
+        );
+
+        let status = Status::from_str(&[WORKING, PARENT].join("\n")).unwrap();
+        assert_eq!(1, status.local_bookmarks().len());
+        assert!(status.remote_bookmarks().is_empty());
+    }
+
+    #[test]
+    fn test_status_from_colored_str() {
+        let colored_file = "\x1b[38;5;6mA src/lib.rs\x1b[39m";
+        let colored_working = "Working copy : \x1b[1m\x1b[38;5;13mq\x1b[0m\x1b[38;5;8mnxonnkx\x1b[39m \x1b[1m\x1b[38;5;12m6\x1b[0m\x1b[38;5;8m0be3879\x1b[39m \x1b[38;5;5mmain\x1b[39m | \x1b[38;5;3m(no description set)\x1b[39m";
+        let input = [HEADER, colored_file, FILE2, colored_working, PARENT].join("\n");
+        let expected = Status::from_str(&[HEADER, FILE1, FILE2, WORKING, PARENT].join("\n"));
+        assert_eq!(expected, Status::from_str(&input));
+    }
+
+    #[test]
+    fn test_colored_error_offset() {
+        let colored_parent = "\x1b[1mParent commit\x1b[0m: \x1b[38;5;5mZZZZ\x1b[39m 00000000";
+        let input = [WORKING, colored_parent].join("\n");
+        let error = Status::from_str(&input).unwrap_err();
+        assert_eq!(input.find("ZZZZ"), error.offset());
+    }
+
+    #[test]
+    fn test_malformed_parent_error() {
+        let input = [WORKING, "Parent commit: ZZZZ 00000000"].join("\n");
+        let error = Status::from_str(&input).unwrap_err();
+        assert_eq!(Some("parent commit"), error.label());
+        assert_eq!(
+            Some(WORKING.len() + 1 + "Parent commit: ".len()),
+            error.offset()
+        );
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.is::<ParseError>());
+        let message = error.to_string();
+        assert!(message.contains("parent commit"), "{message}");
+        assert!(message.contains("change id"), "{message}");
+
+        let input = [WORKING, "Parnet commit: zzzzzzzz 00000000"].join("\n");
+        let message = Status::from_str(&input).unwrap_err().to_string();
+        assert!(message.contains("Parent commit:"), "{message}");
+    }
+
+    #[test]
+    fn test_parse_custom_labels() {
+        let input = [
+            HEADER,
+            FILE1,
+            FILE2,
+            "WC: qnxonnkx 60be3879 main | first line",
+            "  second line",
+            "P: zzzzzzzz 00000000 (empty) (no description set)",
+        ]
+        .join("\n");
+        let options = ParseOptions {
+            working_copy_label: s!("WC:"),
+            parent_label: s!("P"),
+            ..ParseOptions::default()
+        };
+        let status = Status::parse_with_options(&input, &options).unwrap();
+        assert_eq!("qnxonnkx", status.working_copy().unwrap().change_id());
+        assert_eq!(
+            "first line\n  second line",
+            status.working_copy().unwrap().description()
+        );
+        assert_eq!("zzzzzzzz", status.parent_commit().unwrap().change_id());
+        assert_eq!(2, status.file_changes().len());
+
+        assert!(Status::from_str(&input).is_err());
+    }
+
+    #[test]
+    fn test_empty_input() {
+        for input in ["", "\n\n"] {
+            let error = Status::from_str(input).unwrap_err();
+            assert!(matches!(error, Error::EmptyInput), "{input:?}");
+            assert_eq!(None, error.offset());
+        }
+    }
+
+    #[test]
+    fn test_to_hash_map() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let map = Status::from_str(&input).unwrap().to_hash_map();
+
+        let expected: HashMap<String, String> = [
+            ("working_copy_change_id", "qnxonnkx"),
+            ("working_copy_commit_id", "60be3879"),
+            ("working_copy_bookmarks", "main"),
+            ("working_copy_description", "(no description set)"),
+            ("working_copy_empty", "false"),
+            ("parent_change_id", "zzzzzzzz"),
+            ("parent_commit_id", "00000000"),
+            ("parent_bookmarks", ""),
+            ("parent_description", "(no description set)"),
+            ("parent_empty", "true"),
+            ("clean", "false"),
+            ("file_count", "2"),
+            ("added", "2"),
+            ("modified", "0"),
+            ("deleted", "0"),
+            ("renamed", "0"),
+            ("copied", "0"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(expected, map);
+    }
+
+    #[test]
+    fn test_files_with_status() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+
+        let added: Vec<&Path> = status
+            .files_with_status(FileStatus::Added)
+            .map(|change| change.path())
+            .collect();
+        assert_eq!(
+            vec![Path::new("src/lib.rs"), Path::new("src/main.rs")],
+            added
+        );
+        assert_eq!(0, status.files_with_status(FileStatus::Deleted).count());
+    }
+
+    #[test]
+    fn test_parse_multiple_bookmarks() {
+        let mut input = "qnxonnkx 60be3879 main feature-x@origin v1.0 | some description";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!(
+            vec![
+                local_bookmark("main"),
+                remote_bookmark("feature-x", "origin"),
+                local_bookmark("v1.0"),
+            ],
+            details.bookmarks()
+        );
+        assert_eq!("some description", details.description());
+        assert_eq!(
+            "qnxonnkx 60be3879 main feature-x@origin v1.0 | some description",
+            details.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_commit_one_word_description() {
+        let mut input = "Working copy : kxryzmor 3f2c9a1b (empty) feature\nParent commit: zzzzzzzz 00000000 (empty) (no description set)";
+        let commit = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()))
+            .unwrap();
+        assert!(commit.empty());
+        assert!(commit.bookmarks().is_empty());
+        assert_eq!("feature", commit.description());
+        assert!(input.starts_with("\nParent commit"));
+
+        let input = [
+            "The working copy has no changes.",
+            "Working copy : qnxonnkx 60be3879 (empty) wip",
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let working_copy = status.working_copy().unwrap();
+        assert!(working_copy.bookmarks().is_empty());
+        assert_eq!("wip", working_copy.description());
+
+        let mut input = "kxryzmor 3f2c9a1b add parser";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert!(details.bookmarks().is_empty());
+        assert_eq!("add parser", details.description());
+    }
+
+    #[test]
+    fn test_touched_dir_count() {
+        let input = [
+            HEADER,
+            "A README.md",
+            "M src/lib.rs",
+            "A src/main.rs",
+            "D tests/cmd/simple.toml",
+            "R tests/old.rs => tests/cmd/new.rs",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        // The root, src, and tests/cmd
+        assert_eq!(3, status.touched_dir_count());
+    }
+
+    #[test]
+    fn test_hash_set_of_changes() {
+        let input = [HEADER, FILE1, FILE2, FILE1, WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+
+        let changes: HashSet<WorkingCopyChange> = status.file_changes().iter().cloned().collect();
+        assert_eq!(2, changes.len());
+
+        let commits = HashSet::from([
+            status.working_copy().unwrap().clone(),
+            status.parent_commit().unwrap().clone(),
+        ]);
+        assert_eq!(2, commits.len());
+    }
+
+    #[test]
+    fn test_parse_description_containing_pipe() {
+        let mut input = "Working copy : kxryzmor 3f2c9a1b main | fix: a | b";
+        let commit = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()))
+            .unwrap();
+        assert_eq!(&[local_bookmark("main")], commit.bookmarks());
+        assert_eq!("fix: a | b", commit.description());
+        assert_eq!("", input);
+    }
+
+    #[test]
+    fn test_prompt_color_hint() {
+        let parent = "Parent commit: xtryyrqp 75d612e0 main@origin | main branch";
+        let cases = [
+            (
+                vec![HEADER, FILE1, WORKING, parent],
+                RepoState::Dirty,
+                "yellow",
+            ),
+            (vec![WORKING, parent], RepoState::Clean, "green"),
+            (vec![WORKING, PARENT], RepoState::Fresh, "blue"),
+            (
+                vec![
+                    HEADER,
+                    "M src/lib.rs",
+                    WORKING,
+                    parent,
+                    "Warning: There are unresolved conflicts at these paths:",
+                    "src/lib.rs    2-sided conflict",
+                ],
+                RepoState::Conflicted,
+                "red",
+            ),
+        ];
+        for (lines, state, color) in cases {
+            let status = Status::from_str(&lines.join("\n")).unwrap();
+            assert_eq!(state, status.state());
+            assert_eq!(color, status.prompt_color_hint());
+        }
+    }
+
+    #[test]
+    fn test_status_display_round_trip() {
+        let dirty = [HEADER, FILE1, "R src/old.rs => src/new.rs", WORKING, PARENT].join("\n");
+        let clean = ["The working copy has no changes.", WORKING, PARENT].join("\n");
+        for input in [dirty, clean] {
+            let status = Status::from_str(&input).unwrap();
+            assert_eq!(input, status.to_string());
+            assert_eq!(Ok(&status), Status::from_str(&status.to_string()).as_ref());
+        }
+
+        let input = [
+            "Working copy  (second@) : qnxonnkx?? 60be3879 (empty) main feature | wip",
+            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
+            "Hint: Use `jj new` to start a new change.",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(Ok(&status), Status::from_str(&status.to_string()).as_ref());
+    }
+
+    #[test]
+    fn test_parse_commit_markers() {
+        let cases = [
+            ("kxryzmor 3f2c9a1b (empty) main | desc", true, false),
+            ("kxryzmor 3f2c9a1b (conflict) main | desc", false, true),
+            (
+                "kxryzmor 3f2c9a1b (conflict) (empty) main | desc",
+                true,
+                true,
+            ),
+        ];
+        for (input, empty, conflict) in cases {
+            let details = commit_details_ref
+                .parse(input)
+                .map(|details| details.to_owned())
+                .unwrap();
+            assert_eq!(empty, details.empty(), "{input}");
+            assert_eq!(conflict, details.is_conflicted(), "{input}");
+            assert_eq!(&[local_bookmark("main")], details.bookmarks());
+            assert_eq!("desc", details.description());
+            assert_eq!(input, details.to_string());
+        }
+    }
+
+    #[test]
+    fn test_parse_lowercase_file_status() {
+        let input = [
+            HEADER,
+            "a src/lib.rs",
+            "m src/main.rs",
+            "d README.md",
+            "r src/old.rs => src/new.rs",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let statuses: Vec<&FileStatus> = status
+            .file_changes()
+            .iter()
+            .map(|change| change.status())
+            .collect();
+        assert_eq!(
+            vec![
+                &FileStatus::Added,
+                &FileStatus::Modified,
+                &FileStatus::Deleted,
+                &FileStatus::Renamed
+            ],
+            statuses
+        );
+        assert_eq!(Ok(FileStatus::Added), FileStatus::from_str("a"));
+    }
+
+    #[test]
+    fn test_matches_revset_symbol() {
+        let input = [
+            "Working copy : qnxonnkx 60be3879 main | wip",
+            "Parent commit: xtryyrqp 75d612e0 main@origin feature?? | main branch",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let working_copy = status.working_copy().unwrap();
+        let parent = status.parent_commit().unwrap();
+
+        assert!(working_copy.matches_revset_symbol("@"));
+        assert!(!working_copy.matches_revset_symbol("@-"));
+        assert!(parent.matches_revset_symbol("@-"));
+        assert!(!parent.matches_revset_symbol("@"));
+
+        assert!(working_copy.matches_revset_symbol("qnx"));
+        assert!(working_copy.matches_revset_symbol("60be"));
+        assert!(!working_copy.matches_revset_symbol("xtr"));
+        assert!(!working_copy.matches_revset_symbol(""));
+
+        assert!(working_copy.matches_revset_symbol("main"));
+        assert!(!parent.matches_revset_symbol("main"));
+        assert!(parent.matches_revset_symbol("main@origin"));
+        // Conflicted bookmarks don't resolve to a single commit
+        assert!(!parent.matches_revset_symbol("feature"));
+
+        let input = ["Working copy  (second@) : qnxonnkx 60be3879 wip", PARENT].join("\n");
+        let working_copy = Status::from_str(&input)
+            .unwrap()
+            .working_copy()
+            .unwrap()
+            .clone();
+        assert!(working_copy.matches_revset_symbol("@"));
+        assert!(working_copy.matches_revset_symbol("second@"));
+        assert!(!working_copy.matches_revset_symbol("default@"));
+    }
+
+    #[test]
+    fn test_parse_divergent_and_hidden_annotations() {
+        let input = "kxryzmor 3f2c9a1b (hidden) (divergent) main | desc";
+        let details = commit_details_ref
+            .parse(input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert!(details.is_hidden());
+        assert!(details.is_divergent());
+        assert!(!details.empty());
+        assert!(!details.is_conflicted());
+        assert_eq!(&[local_bookmark("main")], details.bookmarks());
+        assert_eq!("desc", details.description());
+
+        let input = "kxryzmor 3f2c9a1b (empty) (hidden) (no description set)";
+        let details = commit_details_ref
+            .parse(input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert!(details.is_hidden());
+        assert!(details.empty());
+        assert!(!details.is_divergent());
+    }
+
+    #[test]
+    fn test_commit_details_new() {
+        let details = CommitDetails::new("zzzzzzzz", "00000000", true, Vec::new(), None);
+        let mut input = "zzzzzzzz 00000000 (empty) (no description set)";
+        assert_eq!(
+            Ok(details),
+            commit_details_ref
+                .parse_next(&mut input)
+                .map(|details| details.to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_wrapped_description() {
+        let input = [
+            "Working copy : qnxonnkx 60be3879 main | a long description that jj",
+            "  wrapped at the terminal width",
+            PARENT,
+        ]
+        .join("\n");
+
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(
+            "a long description that jj\n  wrapped at the terminal width",
+            status.working_copy().unwrap().description()
+        );
+
+        let options = ParseOptions {
+            unwrap_descriptions: true,
+            ..ParseOptions::default()
+        };
+        let status = Status::parse_with_options(&input, &options).unwrap();
+        assert_eq!(
+            "a long description that jj wrapped at the terminal width",
+            status.working_copy().unwrap().description()
+        );
+        assert!(status.parent_commit().unwrap().is_root());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_changed_file_sizes() {
+        let root = std::env::temp_dir().join(format!("jj-status-parser-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+
+        let input = [HEADER, FILE1, FILE2, "D README.md", WORKING, PARENT].join("\n");
+        let sizes = Status::from_str(&input).unwrap().changed_file_sizes(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            vec![
+                (PathBuf::from("src/lib.rs"), Some(12)),
+                (PathBuf::from("src/main.rs"), None),
+                (PathBuf::from("README.md"), None),
+            ],
+            sizes
+        );
+    }
+
+    #[test]
+    fn test_status_try_from_bytes() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        assert_eq!(Status::from_str(&input), Status::try_from(input.as_bytes()));
+
+        let error = Status::try_from(&b"Working copy : \xff"[..]).unwrap_err();
+        assert!(matches!(error, Error::InvalidUtf8 { .. }));
+        assert_eq!(Some(15), error.offset());
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.is::<std::str::Utf8Error>());
+    }
+
+    #[test]
+    fn test_status_from_str_trailing_newline() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let expected = Status::from_str(&input).unwrap();
+        assert_eq!(
+            Ok(&expected),
+            Status::from_str(&format!("{input}\n")).as_ref()
+        );
+        assert_eq!(
+            Ok(&expected),
+            Status::from_str(&format!("{input}\r\n")).as_ref()
+        );
+    }
+
+    #[test]
+    fn test_status_from_str_trailing_blank_lines() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let expected = Status::from_str(&input).unwrap();
+        for trailing in ["\n\n", "\n \n\t\n"] {
+            assert_eq!(
+                Ok(&expected),
+                Status::from_str(&format!("{input}{trailing}")).as_ref()
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_conflict_and_empty_in_either_order() {
+        for markers in ["(conflict) (empty)", "(empty) (conflict)"] {
+            let input = format!("kxryzmor 3f2c9a1b {markers} desc");
+            let details = commit_details_ref
+                .parse(&input)
+                .map(|details| details.to_owned())
+                .unwrap();
+            assert!(details.empty(), "{input}");
+            assert!(details.is_conflicted(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_commit_and_change_ids() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(vec!["60be3879", "00000000"], status.commit_ids());
+        assert_eq!(vec!["qnxonnkx", "zzzzzzzz"], status.change_ids());
+
+        let merge = [
+            HEADER,
+            FILE1,
+            WORKING,
+            "Parent commit: xtryyrqp 75d612e0 main | main branch",
+            "Parent commit: rlvkpnrz a3d80cec feature | feature branch",
+        ]
+        .join("\n");
+        let status = Status::from_str(&merge).unwrap();
+        assert_eq!(
+            vec!["60be3879", "75d612e0", "a3d80cec"],
+            status.commit_ids()
+        );
+        assert_eq!(
+            vec!["qnxonnkx", "xtryyrqp", "rlvkpnrz"],
+            status.change_ids()
+        );
+    }
+
+    #[test]
+    fn test_missing_description_is_empty_description() {
+        let status = Status::from_str(&[WORKING, PARENT].join("\n")).unwrap();
+        assert_eq!(
+            EMPTY_DESCRIPTION,
+            status.parent_commit().unwrap().description()
+        );
+    }
+
+    #[test]
+    fn test_has_description() {
+        let input = [
+            "Working copy : qnxonnkx 60be3879 main | (no description set)",
+            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert!(!status.working_copy().unwrap().has_description());
+        assert!(status.parent_commit().unwrap().has_description());
+    }
+
+    #[test]
+    fn test_working_copy_change_extension() {
+        let cases = [
+            ("A src/lib.rs", Some("rs")),
+            ("A .gitignore", None),
+            ("A dist/foo.tar.gz", Some("gz")),
+            ("A LICENSE", None),
+            ("R src/a.txt => src/b.md", Some("md")),
+        ];
+        for (input, expected) in cases {
+            let change = file_change_ref
+                .parse(input)
+                .map(|change| change.to_owned())
+                .unwrap();
+            assert_eq!(expected, change.extension(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_whitespace_only_description() {
+        for input in [
+            "Working copy : qnxonnkx 60be3879 main |    ",
+            "Working copy : qnxonnkx 60be3879 main | \n   ",
+        ] {
+            let status = Status::from_str(&[input, PARENT].join("\n")).unwrap();
+            assert!(
+                !status.working_copy().unwrap().has_description(),
+                "{input:?}"
+            );
+            assert_eq!(
+                EMPTY_DESCRIPTION,
+                status.working_copy().unwrap().description()
+            );
+        }
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_status_to_ron_string() {
+        let input = [HEADER, FILE1, "R src/old.rs => src/new.rs", WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let ron = status.to_ron_string().unwrap();
+        assert!(ron::from_str::<ron::Value>(&ron).is_ok(), "{ron}");
+        assert_eq!(status, ron::from_str::<Status>(&ron).unwrap());
+    }
+
+    #[test]
+    fn test_parse_one_char_change_id() {
+        let input = ["Working copy : q 60be3879 main | wip", PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!("q", status.working_copy().unwrap().change_id());
+        assert_eq!(Some("60be3879"), status.working_copy().unwrap().commit_id());
+    }
+
+    #[test]
+    fn test_parse_change_id_lengths() {
+        let mut input = "q 6";
+        assert_eq!(Ok("q"), change_id(&mut input));
+        assert_eq!(" 6", input);
+
+        let full_change_id = "qnxonnkxrwvpmzoxlwxqvpvwrpnmqssy";
+        let full_commit_id = "60be38795ad8c1f1e0f6b0f7d5d8a6b1c2f3e4d5";
+        let input = format!("{full_change_id} {full_commit_id} main | wip");
+        let details = commit_details_ref
+            .parse(&input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!(full_change_id, details.change_id());
+        assert_eq!(Some(full_commit_id), details.commit_id());
+    }
+
+    #[test]
+    fn test_into_file_changes() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let changes = Status::from_str(&input).unwrap().into_file_changes();
+        let paths = std::thread::spawn(move || {
+            changes
+                .into_iter()
+                .map(|change| change.path)
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(
+            vec![PathBuf::from("src/lib.rs"), PathBuf::from("src/main.rs")],
+            paths
+        );
+    }
+
+    #[test]
+    fn test_status_without_working_copy() {
+        let status = Status::from_str(PARENT).unwrap();
+        assert_eq!(None, status.working_copy());
+        assert!(status.parent_commit().unwrap().is_root());
+        assert_eq!(vec!["00000000"], status.commit_ids());
+        assert_eq!(Ok(&status), Status::from_str(&status.to_string()).as_ref());
+
+        let input = ["The working copy has no changes.", PARENT].join("\n");
+        assert_eq!(None, Status::from_str(&input).unwrap().working_copy());
+    }
+
+    #[test]
+    fn test_sorted_changes() {
+        let input = [
+            HEADER,
+            "D b.rs",
+            "M z.rs",
+            "A y.rs",
+            "R old.rs => a.rs",
+            "M c.rs",
+            "A x.rs",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let sorted: Vec<String> = status
+            .sorted_changes()
+            .iter()
+            .map(|change| change.to_string())
+            .collect();
+        assert_eq!(
+            vec![
+                "A x.rs",
+                "A y.rs",
+                "M c.rs",
+                "M z.rs",
+                "D b.rs",
+                "R old.rs => a.rs"
+            ],
+            sorted
+        );
+        assert!(FileStatus::Added < FileStatus::Modified);
+        assert!(FileStatus::Modified < FileStatus::Deleted);
+    }
+
+    #[test]
+    fn test_parse_root_prefixed_paths() {
+        let input = [
+            HEADER,
+            "M @/src/lib.rs",
+            "R @/src/old.rs => @/src/new.rs",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let options = ParseOptions {
+            strip_root_prefix: true,
+            ..ParseOptions::default()
+        };
+        let status = Status::parse_with_options(&input, &options).unwrap();
+        let changes = status.file_changes();
+        assert_eq!(Path::new("src/lib.rs"), changes[0].path());
+        assert_eq!(Some(Path::new("src/old.rs")), changes[1].from_path());
+        assert_eq!(Path::new("src/new.rs"), changes[1].path());
+
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(Path::new("@/src/lib.rs"), status.file_changes()[0].path());
+    }
+
+    #[test]
+    fn test_parse_conflicted_bookmarks() {
+        let mut input = "main?? | desc";
+        let expected = Bookmark {
+            conflicted: true,
+            ..local_bookmark("main")
+        };
+        assert_eq!(
+            Ok(vec![expected]),
+            bookmark_column(&mut input)
+                .map(|bookmarks| bookmarks.into_iter().map(BookmarkRef::to_owned).collect())
+        );
+        assert_eq!(" desc", input);
+
+        let mut input = "main??@origin | desc";
+        let expected = Bookmark {
+            conflicted: true,
+            ..remote_bookmark("main", "origin")
+        };
+        assert_eq!(
+            Ok(vec![expected]),
+            bookmark_column(&mut input)
+                .map(|bookmarks| bookmarks.into_iter().map(BookmarkRef::to_owned).collect())
+        );
+        assert_eq!(" desc", input);
+    }
+
+    #[test]
+    fn test_status_without_parent_commit() {
+        let input = [HEADER, FILE1, WORKING].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(None, status.parent_commit());
+        assert_eq!("qnxonnkx", status.working_copy().unwrap().change_id());
+        assert_eq!(Ok(&status), Status::from_str(&status.to_string()).as_ref());
+
+        let input = [WORKING, "Hint: Use `jj new` to start a new change."].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(None, status.parent_commit());
+        assert_eq!(1, status.hints().len());
+    }
+
+    #[test]
+    fn test_parse_deep_path() {
+        let deep = (0..200)
+            .map(|i| format!("dir{i}"))
+            .collect::<Vec<_>>()
+            .join("/")
+            + "/file.rs";
+        let lines: Vec<String> = (0..5_000).map(|_| format!("M {deep}")).collect();
+        let input = [lines.join("\n"), WORKING.to_string(), PARENT.to_string()].join("\n");
+
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(5_000, status.file_changes().len());
+        let path = status.file_changes()[0].path();
+        assert_eq!(201, path.components().count());
+        assert_eq!(Some("rs"), status.file_changes()[0].extension());
+    }
+
+    fn points_into(input: &str, slice: &str) -> bool {
+        input.as_bytes().as_ptr_range().contains(&slice.as_ptr())
+    }
+
+    #[test]
+    fn test_parse_ref_borrows_input() {
+        let input = [
+            HEADER,
+            "A src/lib.rs",
+            "R src/old.rs => src/new.rs",
+            "Working copy : qnxonnkx 60be3879 main feature@origin | add parser",
+            PARENT,
+            "Hint: Use `jj new` to start a new change",
+        ]
+        .join("\n");
+        let status = parse_ref(&input).unwrap();
+
+        let working_copy = status.working_copy().unwrap();
+        assert_eq!("qnxonnkx", working_copy.change_id());
+        assert!(points_into(&input, working_copy.change_id()));
+        assert!(points_into(&input, working_copy.commit_id().unwrap()));
+        let bookmarks = working_copy.bookmarks();
+        assert_eq!(
+            vec!["main", "feature"],
+            bookmarks.iter().map(BookmarkRef::name).collect::<Vec<_>>()
+        );
+        assert_eq!(Some("origin"), bookmarks[1].remote());
+        assert!(points_into(&input, bookmarks[0].name()));
+        assert!(points_into(&input, bookmarks[1].remote().unwrap()));
+        assert_eq!(Some("add parser"), working_copy.description());
+        assert!(points_into(&input, working_copy.description().unwrap()));
+        assert_eq!(None, status.parent_commit().unwrap().description());
+
+        let changes = status.file_changes();
+        assert!(matches!(changes[0].path, Cow::Borrowed(_)));
+        assert!(points_into(&input, changes[0].path()));
+        assert_eq!(Some("src/old.rs"), changes[1].from_path());
+        assert!(points_into(&input, changes[1].from_path().unwrap()));
+        assert!(points_into(&input, status.hints()[0]));
+
+        assert_eq!(Status::from_str(&input).unwrap(), status.to_owned());
+    }
+
+    #[test]
+    fn test_parse_ref_copies_escaped_paths() {
+        let input = [
+            HEADER,
+            r#"A "my file.rs""#,
+            r#"A "say \"hi\".txt""#,
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = parse_ref(&input).unwrap();
+        let changes = status.file_changes();
+        assert_eq!("my file.rs", changes[0].path());
+        assert!(points_into(&input, changes[0].path()));
+        assert_eq!(r#"say "hi".txt"#, changes[1].path());
+        assert!(matches!(changes[1].path, Cow::Owned(_)));
+        assert_eq!(Status::from_str(&input).unwrap(), status.to_owned());
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+        let status = Status::from_reader(io::Cursor::new(input.as_bytes())).unwrap();
+        assert_eq!(Status::from_str(&input).unwrap(), status);
+
+        let error = Status::from_reader(io::Cursor::new(b"\xff")).unwrap_err();
+        assert!(matches!(error, Error::InvalidUtf8 { .. }));
+    }
+
+    #[test]
+    fn test_from_reader_io_error() {
+        struct FailingReader;
+
+        impl io::Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("pipe closed"))
+            }
+        }
+
+        let error = Status::from_reader(FailingReader).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Io {
+                kind: io::ErrorKind::Other,
+                ..
+            }
+        ));
+        assert_eq!("failed to read input: pipe closed", error.to_string());
+        assert_eq!(None, error.offset());
+    }
+
+    #[test]
+    fn test_parse_bookmark_url_remote() {
+        let bookmark = Bookmark::from_str("main@git@github.com:user/repo.git").unwrap();
+        assert_eq!(
+            remote_bookmark("main@git", "github.com:user/repo.git"),
+            bookmark
+        );
+
+        let mut input = "main@https://user@example.com/repo.git main | desc";
+        let expected = vec![
+            remote_bookmark("main@https://user", "example.com/repo.git"),
+            local_bookmark("main"),
+        ];
+        assert_eq!(
+            Ok(expected),
+            bookmark_column(&mut input)
+                .map(|bookmarks| bookmarks.into_iter().map(BookmarkRef::to_owned).collect())
+        );
+        assert_eq!(" desc", input);
+    }
 
     #[test]
-    fn test_parse_change_id() {
-        let mut input = "qnxonnkx";
-        let expected = String::from("qnxonnkx");
-        let actual = change_id(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_anything_to_commit() {
+        let empty = "Working copy : qnxonnkx 60be3879 (empty) (no description set)";
+        let described = "Working copy : qnxonnkx 60be3879 (empty) add parser";
+
+        let clean =
+            Status::from_str(&["The working copy has no changes.", empty, PARENT].join("\n"));
+        assert!(!clean.unwrap().anything_to_commit());
+
+        let described =
+            Status::from_str(&["The working copy has no changes.", described, PARENT].join("\n"));
+        assert!(described.unwrap().anything_to_commit());
+
+        let dirty = Status::from_str(&[HEADER, FILE1, WORKING, PARENT].join("\n"));
+        assert!(dirty.unwrap().anything_to_commit());
     }
 
     #[test]
-    fn test_parse_commit_id() {
-        let mut input = "60be3879";
-        let expected = String::from("60be3879");
-        let actual = commit_id(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_parse_without_commit_id() {
+        let line = "Working copy : qnxonnkx (empty) main | add parser";
+        let input = ["The working copy has no changes.", line, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let working_copy = status.working_copy().unwrap();
+        assert_eq!("qnxonnkx", working_copy.change_id());
+        assert_eq!(None, working_copy.commit_id());
+        assert!(working_copy.empty());
+        assert_eq!(&[local_bookmark("main")], working_copy.bookmarks());
+        assert_eq!("add parser", working_copy.description());
+        assert_eq!(vec!["00000000"], status.commit_ids());
+        assert_eq!(input, status.to_string());
+
+        let mut input = "qnxonnkx (no description set)";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!(None, details.commit_id());
+        assert!(!details.has_description());
     }
 
     #[test]
-    fn test_parse_file_change() {
-        let mut input = FILE1;
-        let expected = WorkingCopyChange {
-            status: FileStatus::Added,
-            path: PathBuf::from("src/lib.rs"),
-        };
-        let actual = file_change(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_parse_without_commit_id_hex_description() {
+        let mut input = "Working copy : qnxonnkx add parser";
+        let working_copy = working_copy_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::WorkingCopy(details.to_owned()))
+            .unwrap();
+        assert_eq!(None, working_copy.commit_id());
+        assert_eq!("add parser", working_copy.description());
+
+        let mut input = "qnxonnkx cafe | deadbeef";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!(None, details.commit_id());
+        assert_eq!(&[local_bookmark("cafe")], details.bookmarks());
+        assert_eq!("deadbeef", details.description());
     }
 
     #[test]
-    fn test_parse_file_changes() {
-        let input = [HEADER, FILE1, FILE2].join("\n");
-        let mut input = input.as_str();
+    fn test_parse_short_commit_id() {
+        let mut input = "Parent commit: zzzzzzzz 0000 (empty) (no description set)";
+        let parent = parent_commit_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::ParentCommit(details.to_owned()))
+            .unwrap();
+        assert_eq!(Some("0000"), parent.commit_id());
+        assert!(parent.empty());
+        assert!(!parent.has_description());
 
-        let expected = vec![
-            WorkingCopyChange {
-                status: FileStatus::Added,
-                path: PathBuf::from("src/lib.rs"),
-            },
-            WorkingCopyChange {
-                status: FileStatus::Added,
-                path: PathBuf::from("src/main.rs"),
-            },
+        let cases = [
+            ("qnxonnkx 60be (empty) fix bug", Some("60be"), "fix bug"),
+            ("qnxonnkx 60be (conflict) fix bug", Some("60be"), "fix bug"),
+            ("qnxonnkx 60be main | fix bug", Some("60be"), "fix bug"),
+            (
+                "qnxonnkx 60be (no description set)",
+                Some("60be"),
+                EMPTY_DESCRIPTION,
+            ),
+            ("qnxonnkx 60be", Some("60be"), EMPTY_DESCRIPTION),
+            ("qnxonnkx 60be fix bug", None, "60be fix bug"),
+            ("qnxonnkx 60be3879 fix bug", Some("60be3879"), "fix bug"),
         ];
-        let actual = file_changes(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+        for (input, commit_id, description) in cases {
+            let details = commit_details_ref
+                .parse(input)
+                .map(|details| details.to_owned())
+                .unwrap();
+            assert_eq!(commit_id, details.commit_id(), "{input}");
+            assert_eq!(description, details.description(), "{input}");
+        }
     }
 
     #[test]
-    fn test_parse_details_1() {
-        let mut input = "qnxonnkx 60be3879 main | (no description set)";
-        let expected = CommitDetails {
-            change_id: String::from("qnxonnkx"),
-            commit_id: String::from("60be3879"),
-            empty: false,
-            bookmark: Some(String::from("main")),
-            description: None,
-        };
-        let actual = commit_details(&mut input);
-        assert_eq!(Ok(expected), actual)
-    }
+    fn test_parse_description_leading_whitespace() {
+        let mut input = "qnxonnkx 60be3879 main |   indented note";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!("  indented note", details.description());
 
-    #[test]
-    fn test_parse_details_2() {
-        let mut input = "zzzzzzzz 00000000 (empty) (no description set)";
-        let expected = CommitDetails {
-            change_id: s!("zzzzzzzz"),
-            commit_id: s!("00000000"),
-            empty: true,
-            bookmark: None,
-            description: None,
-        };
-        let actual = commit_details(&mut input);
-        assert_eq!(Ok(expected), actual)
+        let mut input = "qnxonnkx 60be3879 (empty)  indented note";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert!(details.empty());
+        assert_eq!(" indented note", details.description());
+
+        let line = "Working copy : qnxonnkx 60be3879 main |   indented note";
+        let input = ["The working copy has no changes.", line, PARENT].join("\n");
+        assert_eq!(input, Status::from_str(&input).unwrap().to_string());
     }
 
     #[test]
-    fn test_parse_working_copy() {
-        let mut input = WORKING;
-        let expected = Commit::WorkingCopy(CommitDetails {
-            change_id: s!("qnxonnkx"),
-            commit_id: s!("60be3879"),
-            empty: false,
-            bookmark: Some(String::from("main")),
-            description: None,
-        });
-        let actual = working_copy(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_to_git_porcelain() {
+        let cases = [
+            ("A src/main.rs", " A src/main.rs"),
+            ("M src/lib.rs", " M src/lib.rs"),
+            ("D README.md", " D README.md"),
+            ("R src/old.rs => src/new.rs", "R  src/old.rs -> src/new.rs"),
+            ("C src/old.rs => src/new.rs", "C  src/old.rs -> src/new.rs"),
+            (r#"A "my file.rs""#, r#" A "my file.rs""#),
+            (r#"M "say \"hi\".txt""#, r#" M "say \"hi\".txt""#),
+        ];
+        for (input, expected) in cases {
+            let change = file_change_ref
+                .parse(input)
+                .map(|change| change.to_owned())
+                .unwrap();
+            assert_eq!(expected, change.to_git_porcelain(), "{input}");
+        }
     }
 
     #[test]
-    fn test_parse_empty_description() {
-        let mut input = "(no description set)";
-        let expected = None;
-        let actual = description(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_parse_untracked_paths() {
+        let input = [
+            HEADER,
+            FILE1,
+            "Untracked paths:",
+            "? notes.txt",
+            r#"? "scratch dir/todo.md""#,
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(1, status.file_changes().len());
+        assert_eq!(
+            &[
+                PathBuf::from("notes.txt"),
+                PathBuf::from("scratch dir/todo.md")
+            ],
+            status.untracked()
+        );
+        assert!(!status.is_clean());
+
+        let clean = [
+            "The working copy has no changes.",
+            "Untracked paths:",
+            "? notes.txt",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&clean).unwrap();
+        assert!(status.is_clean());
+        assert_eq!(&[PathBuf::from("notes.txt")], status.untracked());
+        assert_eq!(clean, status.to_string());
     }
 
     #[test]
-    fn test_parse_parent_commit() {
-        let mut input = PARENT;
-        let expected = Commit::ParentCommit(CommitDetails {
-            change_id: s!("zzzzzzzz"),
-            commit_id: s!("00000000"),
-            empty: true,
-            bookmark: None,
-            description: None,
-        });
-        let actual = parent_commit(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_short_ids() {
+        let mut input = "qnxonnkx 60be3879 (no description set)";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!("qnxo", details.change_id_short(4));
+        assert_eq!(Some("60"), details.commit_id_short(2));
+        assert_eq!("", details.change_id_short(0));
+        assert_eq!("qnxonnkx", details.change_id_short(100));
+        assert_eq!(Some("60be3879"), details.commit_id_short(100));
+
+        let mut input = "qnxonnkx (no description set)";
+        let details = commit_details_ref
+            .parse_next(&mut input)
+            .map(|details| details.to_owned())
+            .unwrap();
+        assert_eq!(None, details.commit_id_short(4));
+
+        assert_eq!("é", truncate_chars("éa", 1));
     }
 
     #[test]
-    fn test_status_from_str() {
-        let input = [HEADER, FILE1, FILE2, WORKING, PARENT].join("\n");
+    fn test_parse_changes_without_header() {
+        let input = [FILE1, "M src/main.rs", WORKING, PARENT].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert_eq!(2, status.file_changes().len());
+        assert_eq!(Some("60be3879"), status.working_copy().unwrap().commit_id());
 
-        let expected = Status {
-            file_changes: vec![
-                WorkingCopyChange {
-                    status: FileStatus::Added,
-                    path: PathBuf::from("src/lib.rs"),
-                },
-                WorkingCopyChange {
-                    status: FileStatus::Added,
-                    path: PathBuf::from("src/main.rs"),
-                },
-            ],
-            working_copy: Commit::WorkingCopy(CommitDetails {
-                change_id: s!("qnxonnkx"),
-                commit_id: s!("60be3879"),
-                empty: false,
-                bookmark: Some(s!("main")),
-                description: None,
-            }),
-            parent_commit: Commit::ParentCommit(CommitDetails {
-                change_id: s!("zzzzzzzz"),
-                commit_id: s!("00000000"),
-                empty: true,
-                bookmark: None,
-                description: None,
-            }),
+        // A label that also reads as a change line
+        let options = ParseOptions {
+            working_copy_label: String::from("M wc"),
+            ..ParseOptions::default()
         };
-        let actual = Status::from_str(&input);
-        assert_eq!(Ok(expected), actual);
+        let input = [
+            FILE1,
+            "M wc : qnxonnkx 60be3879 main | (no description set)",
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::parse_with_options(&input, &options).unwrap();
+        assert_eq!(1, status.file_changes().len());
+        assert_eq!("qnxonnkx", status.working_copy().unwrap().change_id());
     }
 
     #[test]
-    fn test_no_changes_status_from_str() {
-        let input = ["The working copy has no changes.", WORKING, PARENT].join("\n");
-
-        let expected = Status {
-            file_changes: Vec::new(),
-            working_copy: Commit::WorkingCopy(CommitDetails {
-                change_id: s!("qnxonnkx"),
-                commit_id: s!("60be3879"),
-                empty: false,
-                bookmark: Some(s!("main")),
-                description: None,
-            }),
-            parent_commit: Commit::ParentCommit(CommitDetails {
-                change_id: s!("zzzzzzzz"),
-                commit_id: s!("00000000"),
-                empty: true,
-                bookmark: None,
-                description: None,
-            }),
+    fn test_summary() {
+        let input = [
+            HEADER,
+            FILE1,
+            FILE2,
+            "M Cargo.toml",
+            "D README.md",
+            "R src/old.rs => src/new.rs",
+            "C src/a.rs => src/b.rs",
+            WORKING,
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let expected = StatusSummary {
+            added: 2,
+            modified: 1,
+            deleted: 1,
+            renamed: 1,
+            copied: 1,
         };
-        let actual = Status::from_str(&input);
-        assert_eq!(Ok(expected), actual);
+        assert_eq!(expected, status.summary());
+        assert_eq!(
+            r#"{"added":2,"modified":1,"deleted":1,"renamed":1,"copied":1}"#,
+            serde_json::to_string(&status.summary()).unwrap()
+        );
+
+        let copied = &status.file_changes()[5];
+        assert_eq!(Some(Path::new("src/a.rs")), copied.from_path());
+        assert_eq!("C src/a.rs => src/b.rs", copied.to_string());
     }
 
     #[test]
-    fn test_parse_working_copy_2() {
-        let mut input = "Working copy : oonwmqxn a3d80cec (no description set)";
-        let expected = Commit::WorkingCopy(CommitDetails {
-            change_id: s!("oonwmqxn"),
-            commit_id: s!("a3d80cec"),
-            empty: false,
-            bookmark: None,
-            description: None,
-        });
-        let actual = working_copy(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_reject_change_id_outside_alphabet() {
+        let mut input = "zzabcxyz 60be3879 (no description set)";
+        assert!(change_id(&mut input).is_err());
+
+        let line = "Working copy : zzabcxyz 60be3879 main | (no description set)";
+        let input = ["The working copy has no changes.", line, PARENT].join("\n");
+        let error = Status::from_str(&input).unwrap_err();
+        assert_eq!(Some("working copy"), error.label());
+        // Points at the `a`
+        assert_eq!(Some(50), error.offset());
+        assert!(
+            error
+                .to_string()
+                .contains("change id of the letters `k` to `z`")
+        );
     }
 
     #[test]
-    fn test_parse_parent_commit_2() {
-        let mut input = "Parent commit: xtryyrqp 75d612e0 main@origin | main branch";
-        let expected = Commit::ParentCommit(CommitDetails {
-            change_id: s!("xtryyrqp"),
-            commit_id: s!("75d612e0"),
-            empty: false,
-            bookmark: Some(s!("main@origin")),
-            description: Some(s!("main branch")),
-        });
-        let actual = parent_commit(&mut input);
-        assert_eq!(Ok(expected), actual);
-        assert_eq!("", input);
+    fn test_parse_trailing_metadata() {
+        let input = [
+            "The working copy has no changes.",
+            "Working copy : qnxonnkx 60be3879 (no description set) alice@example.com",
+            "Parent commit: zzzzzzzz 00000000 (empty) (no description set) root 2024-01-02T10:00:00Z",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let working_copy = status.working_copy().unwrap();
+        assert!(!working_copy.has_description());
+        assert_eq!(Some("alice@example.com"), working_copy.trailing());
+        let parent = status.parent_commit().unwrap();
+        assert_eq!(Some("root"), parent.trailing());
+        assert_eq!(Some("2024-01-02T10:00:00Z"), parent.timestamp());
+        assert_eq!(input, status.to_string());
+
+        // The description ends at the line, leaving the next row for the parent
+        let input = [
+            "The working copy has no changes.",
+            "Working copy : qnxonnkx 60be3879 add parser alice@example.com",
+            PARENT,
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let working_copy = status.working_copy().unwrap();
+        assert_eq!("add parser alice@example.com", working_copy.description());
+        assert_eq!(None, working_copy.trailing());
+        assert_eq!("zzzzzzzz", status.parent_commit().unwrap().change_id());
     }
 
     #[test]
-    fn test_status_from_str_2() {
-        let mut input = r#"Working copy changes:
-M src/lib.rs
-Working copy : oonwmqxn a3d80cec (no description set)
-Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#;
+    fn test_status_builder() {
+        let input = [
+            HEADER,
+            FILE1,
+            "R src/old.rs => src/new.rs",
+            "Untracked paths:",
+            "? notes.txt",
+            WORKING,
+            PARENT,
+            "Hint: Use `jj new` to start a new change",
+        ]
+        .join("\n");
+        let status = Status::builder()
+            .file_change(WorkingCopyChange::new(
+                FileStatus::Added,
+                None,
+                "src/lib.rs",
+            ))
+            .file_change(WorkingCopyChange::new(
+                FileStatus::Renamed,
+                Some(PathBuf::from("src/old.rs")),
+                "src/new.rs",
+            ))
+            .untracked("notes.txt")
+            .working_copy(CommitDetails::new(
+                "qnxonnkx",
+                "60be3879",
+                false,
+                vec![local_bookmark("main")],
+                None,
+            ))
+            .parent_commit(CommitDetails::new(
+                "zzzzzzzz",
+                "00000000",
+                true,
+                Vec::new(),
+                None,
+            ))
+            .hint("Hint: Use `jj new` to start a new change")
+            .build();
+        assert_eq!(Status::from_str(&input).unwrap(), status);
+        assert_eq!(input, status.to_string());
+    }
 
-        let _ = file_changes.parse_next(&mut input).unwrap();
-        assert_eq!(
-            r#"
-Working copy : oonwmqxn a3d80cec (no description set)
-Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#,
-            input
-        );
+    #[test]
+    fn test_parse_no_changes_wording() {
+        let expected =
+            Status::from_str(&["The working copy has no changes.", WORKING, PARENT].join("\n"));
+        for line in [
+            "The working copy has no changes",
+            "The working copy is clean",
+            "The working copy is clean.",
+        ] {
+            let input = [line, WORKING, PARENT].join("\n");
+            assert_eq!(expected, Status::from_str(&input), "{line}");
+        }
+    }
 
-        let _ = newline::<&str, ContextError>
-            .parse_next(&mut input)
-            .unwrap();
-        assert_eq!(
-            r#"Working copy : oonwmqxn a3d80cec (no description set)
-Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#,
-            input
-        );
+    #[test]
+    fn test_parse_bookmark_with_slashes_and_dots() {
+        let bookmark = Bookmark::from_str("release/1.2@origin").unwrap();
+        assert_eq!(remote_bookmark("release/1.2", "origin"), bookmark);
+        assert_eq!("release/1.2@origin", bookmark.to_string());
 
-        let foo = working_copy.parse_next(&mut input).unwrap();
-        assert_eq!(
-            Commit::WorkingCopy(CommitDetails {
-                change_id: s!("oonwmqxn"),
-                commit_id: s!("a3d80cec"),
-                empty: false,
-                bookmark: None,
-                description: None
-            }),
-            foo
-        );
-        assert_eq!(
-            r#"
-Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#,
-            input
-        );
+        let bookmark = Bookmark::from_str("release/1.2@v2@origin").unwrap();
+        assert_eq!(remote_bookmark("release/1.2@v2", "origin"), bookmark);
+        assert_eq!("release/1.2@v2@origin", bookmark.to_string());
 
-        let _ = newline::<&str, ContextError>
-            .parse_next(&mut input)
-            .unwrap();
+        let mut input = "feature/foo release/1.2@origin | wip";
+        let expected = vec![
+            local_bookmark("feature/foo"),
+            remote_bookmark("release/1.2", "origin"),
+        ];
         assert_eq!(
-            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
-            input
+            Ok(expected),
+            bookmark_column(&mut input)
+                .map(|bookmarks| bookmarks.into_iter().map(BookmarkRef::to_owned).collect())
         );
+        assert_eq!(" wip", input);
+    }
 
-        let _ = parent_commit.parse_next(&mut input).unwrap();
-        assert_eq!("", input);
-
-        let input = r#"Working copy changes:
-M src/lib.rs
-Working copy : oonwmqxn a3d80cec (no description set)
-Parent commit: xtryyrqp 75d612e0 main@origin | main branch"#;
-
-        // TODO: bookmark should be a struct with branch name and Option<Remote>
+    #[test]
+    fn test_parse_parent_commits() {
+        let input = ["The working copy has no changes.", WORKING].join("\n");
+        let status = Status::from_str(&input).unwrap();
+        assert!(status.parent_commits().is_empty());
+        assert_eq!(None, status.parent_commit());
+        assert_eq!("qnxonnkx", status.working_copy().unwrap().change_id());
 
-        let expected = Status {
-            file_changes: vec![WorkingCopyChange {
-                status: FileStatus::Modified,
-                path: PathBuf::from("src/lib.rs"),
-            }],
-            working_copy: Commit::WorkingCopy(CommitDetails {
-                change_id: s!("oonwmqxn"),
-                commit_id: s!("a3d80cec"),
-                empty: false,
-                bookmark: None,
-                description: None,
-            }),
-            parent_commit: Commit::ParentCommit(CommitDetails {
-                change_id: s!("xtryyrqp"),
-                commit_id: s!("75d612e0"),
-                empty: false,
-                bookmark: Some(s!("main@origin")),
-                description: Some(s!("main branch")),
-            }),
-        };
-        let actual = Status::from_str(&input);
-        assert_eq!(Ok(expected), actual);
+        let input = [
+            "The working copy has no changes.",
+            WORKING,
+            "Parent commit: xtryyrqp 75d612e0 main@origin | main branch",
+            "Parent commit: oonwmqxn a3d80cec feature | add parser",
+            "Hint: Use `jj new` to start a new change",
+        ]
+        .join("\n");
+        let status = Status::from_str(&input).unwrap();
+        let change_ids: Vec<&str> = status
+            .parent_commits()
+            .iter()
+            .map(Commit::change_id)
+            .collect();
+        assert_eq!(vec!["xtryyrqp", "oonwmqxn"], change_ids);
+        assert_eq!("xtryyrqp", status.parent_commit().unwrap().change_id());
+        assert_eq!(1, status.hints().len());
+        assert_eq!(input, status.to_string());
+        assert_eq!(2, parse_ref(&input).unwrap().parent_commits().len());
     }
 
     #[test]
-    fn test_status_no_changes() {
-        let input = [WORKING, PARENT].join("\n");
-        let expected = Status {
-            file_changes: Vec::new(),
-            working_copy: Commit::WorkingCopy(CommitDetails {
-                change_id: s!("qnxonnkx"),
-                commit_id: s!("60be3879"),
-                empty: false,
-                bookmark: Some(s!("main")),
-                description: None,
-            }),
-            parent_commit: Commit::ParentCommit(CommitDetails {
-                change_id: s!("zzzzzzzz"),
-                commit_id: s!("00000000"),
-                empty: true,
-                bookmark: None,
-                description: None,
-            }),
-        };
-        let actual = Status::from_str(&input);
-        assert_eq!(Ok(expected), actual);
+    fn test_parse_one_word_description() {
+        for line in [
+            "Working copy : qnxonnkx 60be3879 wip",
+            "Working copy : qnxonnkx 60be3879 (conflict) wip",
+        ] {
+            let mut input = line;
+            let actual = working_copy_ref(&ParseOptions::default())
+                .parse_next(&mut input)
+                .map(|details| Commit::WorkingCopy(details.to_owned()))
+                .unwrap();
+            assert!(actual.bookmarks().is_empty(), "{line}");
+            assert_eq!("wip", actual.description(), "{line}");
+            assert!(actual.has_description(), "{line}");
+        }
+
+        let mut input = "Parent commit: xtryyrqp 75d612e0 initial";
+        let actual = parent_commit_ref(&ParseOptions::default())
+            .parse_next(&mut input)
+            .map(|details| Commit::ParentCommit(details.to_owned()))
+            .unwrap();
+        assert!(actual.bookmarks().is_empty());
+        assert_eq!("initial", actual.description());
     }
 }
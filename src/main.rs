@@ -1,13 +1,19 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use clap::Parser;
-use jj_status_parser::Status;
+use jj_status_parser::{Commit, Status};
 use log::debug;
+use std::fs;
 use std::io;
-use std::io::BufRead;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
 use std::str::FromStr;
 
 #[derive(Parser)]
 struct Cli {
+    /// File containing `jj status` output. Reads stdin when omitted
+    path: Option<PathBuf>,
+
     /// Operate on the parent commit instead of the working copy
     #[clap(short, long)]
     parent: bool,
@@ -21,12 +27,16 @@ struct Cli {
     #[clap(short, long, group = "output")]
     json: bool,
 
+    /// Output the whole status as json, including the file changes and both commits
+    #[clap(long, group = "output")]
+    json_full: bool,
+
     /// Show the commit id
     #[clap(long)]
     #[arg(group = "output")]
     commit_id: bool,
 
-    /// Show the bookmark (if exists)
+    /// Show the bookmarks (if any), space separated
     #[clap(short, long)]
     #[arg(group = "output")]
     bookmark: bool,
@@ -35,38 +45,160 @@ struct Cli {
     #[clap(short, long)]
     #[arg(group = "output")]
     description: bool,
+
+    /// Show the changed files, one per line
+    #[clap(long)]
+    #[arg(group = "output")]
+    files: bool,
+
+    /// Show the changed files, each terminated by a NUL byte instead of a newline
+    #[clap(long)]
+    #[arg(group = "output")]
+    files0: bool,
+
+    /// Show the number of changed files
+    #[clap(long)]
+    #[arg(group = "output")]
+    count: bool,
+
+    /// Print nothing, exiting with 0 if the working copy is clean and 1 if it has changes
+    #[clap(short, long, visible_alias = "check")]
+    #[arg(group = "output")]
+    quiet: bool,
+
+    /// Render a template, substituting `{change_id}`, `{commit_id}`, `{bookmark}`,
+    /// `{description}`, `{empty}` and `{files}`
+    #[clap(long, value_name = "TEMPLATE")]
+    #[arg(group = "output")]
+    format: Option<String>,
+
+    /// Like `--format`, but read the template from a file
+    #[clap(long, value_name = "PATH")]
+    #[arg(group = "output")]
+    format_file: Option<PathBuf>,
+}
+
+fn join(items: &[impl ToString]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn render(template: &str, status: &Status, change: &Commit) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            bail!("Unclosed `{{` in format template");
+        };
+        let token = &rest[start + 1..start + end];
+        match token {
+            "change_id" => rendered.push_str(change.change_id()),
+            "commit_id" => rendered.push_str(change.commit_id().unwrap_or_default()),
+            "bookmark" => rendered.push_str(&join(change.bookmarks())),
+            "description" => rendered.push_str(change.description()),
+            "empty" => rendered.push_str(&change.empty().to_string()),
+            "files" => {
+                let files = status
+                    .changed_paths()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>();
+                rendered.push_str(&files.join(" "));
+            }
+            _ => bail!("Unknown format token `{{{token}}}`"),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
 }
 
 fn main() -> Result<()> {
     env_logger::init();
     let args = Cli::parse();
 
-    let stdin: Vec<String> = io::stdin()
-        .lock()
-        .lines()
-        .collect::<std::result::Result<Vec<_>, _>>()?;
-    let stdin = stdin.join("\n");
+    let input = match &args.path {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        None => io::read_to_string(io::stdin().lock())?,
+    };
+    let input = input.lines().collect::<Vec<_>>().join("\n");
 
-    let status = Status::from_str(&stdin)?;
+    let status = Status::from_str(&input)?;
     debug!("{status}");
 
+    if args.quiet {
+        process::exit(if status.is_clean() { 0 } else { 1 });
+    }
+
+    if args.files {
+        for change in status.file_changes() {
+            println!("{change}");
+        }
+        return Ok(());
+    }
+
+    if args.files0 {
+        let mut stdout = io::stdout().lock();
+        for change in status.file_changes() {
+            write!(stdout, "{change}\0")?;
+        }
+        return Ok(());
+    }
+
+    if args.json_full {
+        let mut stdout = io::stdout().lock();
+        status.to_json_writer(&mut stdout)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+
+    if args.count {
+        println!("{}", status.file_changes().len());
+        return Ok(());
+    }
+
     let change = if args.parent {
-        &status.parent_commit()
+        status
+            .parent_commit()
+            .context("No parent commit in status output")?
     } else {
-        &status.working_copy()
+        status
+            .working_copy()
+            .context("No working copy in status output")?
     };
 
-    let display = if args.json {
-        &serde_json::to_string(&change)?
-    } else if args.change_id {
+    if let Some(template) = &args.format {
+        println!("{}", render(template, &status, change)?);
+        return Ok(());
+    }
+
+    if let Some(path) = &args.format_file {
+        let template = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read format file {}", path.display()))?;
+        let template = template.strip_suffix('\n').unwrap_or(&template);
+        println!("{}", render(template, &status, change)?);
+        return Ok(());
+    }
+
+    if args.json {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer(&mut stdout, change)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+
+    let display = if args.change_id {
         change.change_id()
     } else if args.commit_id {
-        change.commit_id()
+        change
+            .commit_id()
+            .context("No commit id in status output")?
     } else if args.bookmark {
-        match &change.bookmark() {
-            Some(bookmark) => bookmark,
-            None => "",
-        }
+        &join(change.bookmarks())
     } else if args.description {
         change.description()
     } else {